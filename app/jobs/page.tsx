@@ -23,6 +23,8 @@ import {
   CheckCircle,
   AlertCircle,
   Clock,
+  Lock,
+  Users,
 } from "lucide-react";
 import { stroopsToXlm } from "@/lib/stellar-client";
 import Link from "next/link";
@@ -101,6 +103,42 @@ export default function JobsPage() {
             <AlertCircle className="mr-1 h-3 w-3" /> Disputed
           </Badge>
         );
+      case JobStatus.Released:
+        return (
+          <Badge
+            variant="outline"
+            className="bg-emerald-500/10 text-emerald-600 border-emerald-500/20"
+          >
+            <CheckCircle className="mr-1 h-3 w-3" /> Released
+          </Badge>
+        );
+      case JobStatus.Resolved:
+        return (
+          <Badge
+            variant="outline"
+            className="bg-blue-500/10 text-blue-600 border-blue-500/20"
+          >
+            <CheckCircle className="mr-1 h-3 w-3" /> Resolved
+          </Badge>
+        );
+      case JobStatus.Locked:
+        return (
+          <Badge
+            variant="outline"
+            className="bg-gray-500/10 text-gray-600 border-gray-500/20"
+          >
+            <Lock className="mr-1 h-3 w-3" /> Locked
+          </Badge>
+        );
+      case JobStatus.Funding:
+        return (
+          <Badge
+            variant="outline"
+            className="bg-purple-500/10 text-purple-600 border-purple-500/20"
+          >
+            <Users className="mr-1 h-3 w-3" /> Funding
+          </Badge>
+        );
       default:
         return <Badge variant="outline">Unknown</Badge>;
     }
@@ -252,7 +290,15 @@ export default function JobsPage() {
 
                   {job.status === JobStatus.Completed && (
                     <div className="mt-4 p-3 rounded-md bg-green-500/10 border border-green-500/20 text-sm">
-                      <strong>Completed:</strong> Payment released to agent
+                      <strong>Completed:</strong> Payment is held until the
+                      challenge window ends
+                      {job.release_at && <> at {formatDate(job.release_at)}</>}.
+                    </div>
+                  )}
+
+                  {job.status === JobStatus.Released && (
+                    <div className="mt-4 p-3 rounded-md bg-emerald-500/10 border border-emerald-500/20 text-sm">
+                      <strong>Released:</strong> Payment credited to the agent
                       owner.
                     </div>
                   )}
@@ -1,6 +1,6 @@
 "use client";
 
-import { useState, useEffect } from "react";
+import { useState, useEffect, useRef } from "react";
 import { Agent } from "@/types/agent";
 import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
@@ -57,6 +57,9 @@ export function HireAgentModal({
   const [errors, setErrors] = useState<string[]>([]);
   const [generalError, setGeneralError] = useState<string | null>(null);
   const [jobId, setJobId] = useState<number | null>(null);
+  // Reference for this hire, kept across retries so a resubmit after a
+  // timeout finds the escrow job it already funded
+  const hireRef = useRef<string | null>(null);
 
   useEffect(() => {
     let targetAgents: Agent[] = [];
@@ -71,6 +74,12 @@ export function HireAgentModal({
     }
   }, [agent, agentsProp, isOpen]);
 
+  useEffect(() => {
+    if (!isOpen) {
+      hireRef.current = null;
+    }
+  }, [isOpen]);
+
   if (!isOpen) return null;
 
   const currentAgent =
@@ -129,21 +138,31 @@ export function HireAgentModal({
       // 3. Create escrow job on blockchain
       setLoadingStep("Creating escrow job on Stellar...");
 
-      // For simplicity, use first agent's owner as payment recipient
+      // The escrow pays the first agent's owner as listed in the registry
       // In production, handle multiple agents appropriately
-      const agentOwner = walletAddress; // TODO: Get actual agent owner from registry
       const amountStroops = xlmToStroops(totalCost);
 
       // Native token address (XLM)
       const nativeTokenAddress =
         "CDLZFC3SYJYDZT7K67VZ75HPJVIEUVNIXF47ZG2FB2RMQQVU2HHGCYSC";
 
+      // The escrow returns the existing job for a repeated (hirer, clientRef)
+      if (!hireRef.current) {
+        hireRef.current = crypto.randomUUID();
+      }
+      const clientRef = new Uint8Array(
+        await crypto.subtle.digest(
+          "SHA-256",
+          new TextEncoder().encode(hireRef.current),
+        ),
+      );
+
       const createdJobId = await escrowContract.createJob(
         walletAddress,
-        agentOwner,
         agents[0].id,
         amountStroops,
         nativeTokenAddress,
+        clientRef,
       );
 
       setJobId(createdJobId);
@@ -235,6 +254,9 @@ export function HireAgentModal({
         walletAddress,
       );
 
+      // The hire went through; another submit is a new hire
+      hireRef.current = null;
+
       // 6. Display results
       if (data.results) {
         setResults(data.results);
@@ -0,0 +1,167 @@
+use soroban_sdk::{contractimpl, contracttype, Address, BytesN, Env, String, Symbol};
+
+use crate::{
+    AgentEscrowContract, AgentEscrowContractArgs, AgentEscrowContractClient, DataKey, Funding, JobKind,
+};
+
+/// Budget a hirer grants a spender for opening jobs on their behalf
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Allowance {
+    pub budget: i128,
+    pub period: u64,
+    pub period_start: u64,
+    pub spent: i128,
+}
+
+#[contractimpl]
+impl AgentEscrowContract {
+    /// Let a spender open jobs funded by the hirer, up to a budget per period
+    ///
+    /// The hirer must also approve the escrow on the token contract so it can
+    /// pull the funds. Replaces any existing grant and restarts its period.
+    ///
+    /// # Arguments
+    /// * `hirer` - Address whose funds the spender may use
+    /// * `spender` - Address allowed to call `create_job_for`
+    /// * `token` - Token the budget is denominated in
+    /// * `budget` - Maximum total job amount per period
+    /// * `period` - Length of a budget period in seconds
+    pub fn approve_spender(
+        env: Env,
+        hirer: Address,
+        spender: Address,
+        token: Address,
+        budget: i128,
+        period: u64,
+    ) {
+        hirer.require_auth();
+
+        assert!(budget > 0, "Budget must be positive");
+        assert!(period > 0, "Period must be positive");
+
+        let allowance = Allowance {
+            budget,
+            period,
+            period_start: env.ledger().timestamp(),
+            spent: 0,
+        };
+        env.storage().persistent().set(
+            &DataKey::Allowance(hirer.clone(), spender.clone(), token.clone()),
+            &allowance,
+        );
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "spender_approved"), hirer),
+            (spender, token, budget, period),
+        );
+    }
+
+    /// Withdraw a spender's grant
+    ///
+    /// # Arguments
+    /// * `hirer` - Address that granted the allowance
+    /// * `spender` - Address losing access
+    /// * `token` - Token of the allowance
+    pub fn revoke_spender(env: Env, hirer: Address, spender: Address, token: Address) {
+        hirer.require_auth();
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Allowance(hirer.clone(), spender.clone(), token.clone()));
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "spender_revoked"), hirer),
+            (spender, token),
+        );
+    }
+
+    /// Create a job on a hirer's behalf, paid from the spender's allowance
+    ///
+    /// The job has no verifier, since only the hirer can accept one.
+    ///
+    /// # Arguments
+    /// * `spender` - Address holding an allowance from the hirer
+    /// * `hirer` - Address of the person hiring the agent
+    /// * `agent_id` - Unique identifier of the agent being hired; paid to its registry owner
+    /// * `amount` - Payment amount, counted against the allowance; for agents priced
+    ///   in another currency, the most the spender will pay after conversion
+    /// * `token` - Token contract address for payment
+    /// * `client_ref` - Optional idempotency key; repeating it returns the existing job ID
+    /// * `inputs_hash` - Optional hash of the inputs given to the agent
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_job_for(
+        env: Env,
+        spender: Address,
+        hirer: Address,
+        agent_id: String,
+        amount: i128,
+        token: Address,
+        client_ref: Option<BytesN<32>>,
+        inputs_hash: Option<BytesN<32>>,
+    ) -> u64 {
+        Self::require_not_paused(&env);
+
+        spender.require_auth();
+
+        // Retries return the existing job before the price is converted again
+        if let Some(job_id) = Self::existing_job(&env, &hirer, &client_ref) {
+            return job_id;
+        }
+
+        let amount = Self::job_amount(&env, &agent_id, &token, amount);
+        let agent = Self::registry_client(&env).get_agent(&agent_id);
+        let job_id = Self::open_job(
+            &env,
+            hirer,
+            agent.owner,
+            agent_id.clone(),
+            amount,
+            token,
+            client_ref,
+            Funding::Spender(spender),
+            JobKind::Fixed,
+        );
+        Self::record_verification(&env, job_id, &agent_id, inputs_hash, None);
+
+        job_id
+    }
+
+    /// Get a spender's allowance as of now (`None` if there is no grant)
+    pub fn get_allowance(env: Env, hirer: Address, spender: Address, token: Address) -> Option<Allowance> {
+        let allowance: Allowance = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Allowance(hirer, spender, token))?;
+        Some(Self::roll_period(&env, allowance))
+    }
+}
+
+impl AgentEscrowContract {
+    pub(crate) fn spend_allowance(env: &Env, hirer: &Address, spender: &Address, token: &Address, amount: i128) {
+        let key = DataKey::Allowance(hirer.clone(), spender.clone(), token.clone());
+        let allowance: Allowance = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .expect("No allowance for spender");
+
+        let mut allowance = Self::roll_period(env, allowance);
+        assert!(amount > 0, "Amount must be positive");
+        assert!(allowance.spent + amount <= allowance.budget, "Allowance exceeded");
+        allowance.spent += amount;
+        env.storage().persistent().set(&key, &allowance);
+    }
+
+    fn roll_period(env: &Env, mut allowance: Allowance) -> Allowance {
+        // Start a fresh budget once the current period is over
+        let elapsed = env.ledger().timestamp() - allowance.period_start;
+        if elapsed >= allowance.period {
+            allowance.period_start += elapsed - elapsed % allowance.period;
+            allowance.spent = 0;
+        }
+        allowance
+    }
+}
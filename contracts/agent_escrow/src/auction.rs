@@ -0,0 +1,109 @@
+use soroban_sdk::{contractimpl, contracttype, Address, Env, String, Symbol, Vec};
+
+use crate::tasks::{ClaimMode, TaskStatus};
+use crate::{AgentEscrowContract, AgentEscrowContractArgs, AgentEscrowContractClient, DataKey};
+
+/// Agent owner's offer for an auction task
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Bid {
+    pub agent_id: String,
+    pub price: i128,
+    pub eta: u64,
+    pub submitted_at: u64,
+}
+
+#[contractimpl]
+impl AgentEscrowContract {
+    /// Bid on an auction task, replacing the agent's earlier bid
+    ///
+    /// # Arguments
+    /// * `caller` - Owner of the agent in the registry
+    /// * `task_id` - ID of the auction task
+    /// * `agent_id` - Agent that will do the task
+    /// * `price` - Price asked, at most the task budget
+    /// * `eta` - Promised delivery time
+    pub fn submit_bid(env: Env, caller: Address, task_id: u64, agent_id: String, price: i128, eta: u64) {
+        Self::require_not_paused(&env);
+
+        caller.require_auth();
+
+        let task = Self::get_task(env.clone(), task_id);
+        assert!(task.status == TaskStatus::Open, "Task is not open");
+        assert!(task.mode == ClaimMode::Auction, "Task is not an auction");
+        assert!(env.ledger().timestamp() <= task.deadline, "Bidding has closed");
+        assert!(price > 0, "Price must be positive");
+        assert!(price <= task.reward, "Bid exceeds budget");
+
+        let agent = Self::registry_client(&env).get_agent(&agent_id);
+        assert!(
+            Self::is_agent_operator(&env, &agent_id, &caller),
+            "Caller is not the agent owner"
+        );
+        Self::require_eligible_agent(&env, &task, &agent);
+
+        let mut bidders = Self::get_task_claims(env.clone(), task_id);
+        if !bidders.contains(&agent_id) {
+            bidders.push_back(agent_id.clone());
+            env.storage().persistent().set(&DataKey::TaskClaims(task_id), &bidders);
+        }
+        let bid = Bid {
+            agent_id: agent_id.clone(),
+            price,
+            eta,
+            submitted_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::Bid(task_id, agent_id.clone()), &bid);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "bid_submitted"), task_id),
+            (agent_id, price, eta),
+        );
+    }
+
+    /// Accept a bid once bidding has closed
+    ///
+    /// The winner gets a job at the bid price and the rest of the budget is
+    /// refunded to the hirer.
+    ///
+    /// # Arguments
+    /// * `task_id` - ID of the auction task
+    /// * `agent_id` - Agent whose bid wins
+    pub fn accept_bid(env: Env, task_id: u64, agent_id: String) -> u64 {
+        Self::require_not_paused(&env);
+
+        let mut task = Self::get_task(env.clone(), task_id);
+
+        // Only the hirer can pick the winner
+        task.hirer.require_auth();
+
+        assert!(task.status == TaskStatus::Open, "Task is not open");
+        assert!(task.mode == ClaimMode::Auction, "Task is not an auction");
+        assert!(env.ledger().timestamp() > task.deadline, "Bidding is still open");
+        let bid: Bid = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Bid(task_id, agent_id.clone()))
+            .expect("Bid not found");
+
+        // The listing may have changed since the bid
+        let agent = Self::registry_client(&env).get_agent(&agent_id);
+        Self::require_eligible_agent(&env, &task, &agent);
+
+        Self::assign_task(&env, &mut task, agent, bid.price)
+    }
+
+    /// Get all bids on an auction task
+    pub fn get_bids(env: Env, task_id: u64) -> Vec<Bid> {
+        let mut bids = Vec::new(&env);
+        for agent_id in Self::get_task_claims(env.clone(), task_id) {
+            if let Some(bid) = env.storage().persistent().get(&DataKey::Bid(task_id, agent_id)) {
+                bids.push_back(bid);
+            }
+        }
+        bids
+    }
+}
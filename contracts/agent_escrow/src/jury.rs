@@ -0,0 +1,450 @@
+use soroban_sdk::{contractimpl, contracttype, token, Address, Bytes, BytesN, Env, Symbol, Vec};
+
+use crate::{AgentEscrowContract, AgentEscrowContractArgs, AgentEscrowContractClient, DataKey, JobStatus};
+
+/// Juror verdict on a disputed job
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Verdict {
+    Hirer,
+    Agent,
+}
+
+/// State of a jury case
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum JuryOutcome {
+    Voting,
+    Hirer,
+    Agent,
+    NoMajority,
+}
+
+/// Jury settings, changed through the admin timelock
+///
+/// `panel_size` should be odd to avoid ties, `slash_bps` is the share of stake
+/// slashed from jurors outside the majority and `stake_token` cannot change
+/// once set.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JuryConfig {
+    pub stake_token: Address,
+    pub min_stake: i128,
+    pub panel_size: u32,
+    pub commit_period: u64,
+    pub reveal_period: u64,
+    pub slash_bps: u32,
+}
+
+/// Panel drawn for a disputed job
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JuryCase {
+    pub panel: Vec<Address>,
+    pub commit_deadline: u64,
+    pub reveal_deadline: u64,
+    pub outcome: JuryOutcome,
+}
+
+#[contractimpl]
+impl AgentEscrowContract {
+    /// Stake tokens to join the juror pool
+    ///
+    /// # Arguments
+    /// * `juror` - Address of the juror
+    /// * `amount` - Amount of the stake token to add
+    pub fn join_jury(env: Env, juror: Address, amount: i128) {
+        Self::require_not_paused(&env);
+
+        juror.require_auth();
+        assert!(amount > 0, "Amount must be positive");
+
+        let config = Self::get_jury_config(env.clone());
+        let client = token::Client::new(&env, &config.stake_token);
+        client.transfer(&juror, &env.current_contract_address(), &amount);
+        Self::adjust_total(&env, DataKey::TotalStaked(config.stake_token.clone()), amount);
+
+        let stake = Self::get_juror_stake(env.clone(), juror.clone()) + amount;
+        env.storage()
+            .persistent()
+            .set(&DataKey::JurorStake(juror.clone()), &stake);
+        if stake >= config.min_stake {
+            Self::add_to_juror_pool(&env, &juror);
+        }
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "juror_joined"), juror),
+            stake,
+        );
+    }
+
+    /// Withdraw stake; not possible while sitting on an undecided panel
+    ///
+    /// # Arguments
+    /// * `juror` - Address of the juror
+    /// * `amount` - Amount of stake to withdraw
+    pub fn leave_jury(env: Env, juror: Address, amount: i128) {
+        juror.require_auth();
+        assert!(amount > 0, "Amount must be positive");
+
+        let active_cases: u32 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::JurorActiveCases(juror.clone()))
+            .unwrap_or(0);
+        assert!(active_cases == 0, "Juror has active cases");
+
+        let stake = Self::get_juror_stake(env.clone(), juror.clone());
+        assert!(amount <= stake, "Insufficient stake");
+
+        let config = Self::get_jury_config(env.clone());
+        let remaining = stake - amount;
+        env.storage()
+            .persistent()
+            .set(&DataKey::JurorStake(juror.clone()), &remaining);
+        if remaining < config.min_stake {
+            Self::remove_from_juror_pool(&env, &juror);
+        }
+
+        let client = token::Client::new(&env, &config.stake_token);
+        client.transfer(&env.current_contract_address(), &juror, &amount);
+        Self::adjust_total(&env, DataKey::TotalStaked(config.stake_token.clone()), -amount);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "juror_left"), juror),
+            remaining,
+        );
+    }
+
+    /// Hand a dispute to a randomly drawn jury instead of the arbiter
+    ///
+    /// # Arguments
+    /// * `caller` - Hirer or agent owner of the disputed job
+    /// * `job_id` - ID of the disputed job
+    pub fn escalate_to_jury(env: Env, caller: Address, job_id: u64) {
+        Self::require_not_paused(&env);
+
+        caller.require_auth();
+
+        let job = Self::load_job(&env, job_id);
+        assert!(
+            caller == job.hirer || Self::is_agent_operator(&env, &job.agent_id, &caller),
+            "Only hirer or agent owner can escalate"
+        );
+        assert!(job.status == JobStatus::Disputed, "Job is not disputed");
+        assert!(
+            !env.storage().persistent().has(&DataKey::JuryCase(job_id)),
+            "Dispute already escalated"
+        );
+
+        // Parties to the job can never judge it
+        let config = Self::get_jury_config(env.clone());
+        let mut candidates = Vec::new(&env);
+        for juror in Self::get_jurors(env.clone()).iter() {
+            if juror != job.hirer && juror != job.agent_owner {
+                candidates.push_back(juror);
+            }
+        }
+        assert!(candidates.len() >= config.panel_size, "Not enough jurors");
+
+        env.prng().shuffle(&mut candidates);
+        let panel = candidates.slice(0..config.panel_size);
+        for juror in panel.iter() {
+            let key = DataKey::JurorActiveCases(juror);
+            let active_cases: u32 = env.storage().persistent().get(&key).unwrap_or(0);
+            env.storage().persistent().set(&key, &(active_cases + 1));
+        }
+
+        let now = env.ledger().timestamp();
+        let case = JuryCase {
+            panel: panel.clone(),
+            commit_deadline: now + config.commit_period,
+            reveal_deadline: now + config.commit_period + config.reveal_period,
+            outcome: JuryOutcome::Voting,
+        };
+        env.storage().persistent().set(&DataKey::JuryCase(job_id), &case);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "jury_drawn"), job_id),
+            panel,
+        );
+    }
+
+    /// Commit a hidden vote
+    ///
+    /// # Arguments
+    /// * `juror` - Panel member casting the vote
+    /// * `job_id` - ID of the disputed job
+    /// * `commitment` - `sha256([v] ++ salt)` where `v` is 0 for `Hirer`, 1 for `Agent`
+    pub fn commit_vote(env: Env, juror: Address, job_id: u64, commitment: BytesN<32>) {
+        Self::require_not_paused(&env);
+
+        juror.require_auth();
+
+        let case = Self::get_jury_case(env.clone(), job_id);
+        assert!(case.panel.contains(&juror), "Not on the panel");
+        assert!(
+            env.ledger().timestamp() < case.commit_deadline,
+            "Commit phase has ended"
+        );
+
+        let key = DataKey::JuryCommit(job_id, juror.clone());
+        assert!(!env.storage().persistent().has(&key), "Vote already committed");
+        env.storage().persistent().set(&key, &commitment);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "vote_committed"), job_id),
+            juror,
+        );
+    }
+
+    /// Reveal a previously committed vote
+    ///
+    /// # Arguments
+    /// * `juror` - Panel member revealing the vote
+    /// * `job_id` - ID of the disputed job
+    /// * `verdict` - Vote that was committed
+    /// * `salt` - Salt that was committed
+    pub fn reveal_vote(env: Env, juror: Address, job_id: u64, verdict: Verdict, salt: BytesN<32>) {
+        Self::require_not_paused(&env);
+
+        juror.require_auth();
+
+        let case = Self::get_jury_case(env.clone(), job_id);
+        let now = env.ledger().timestamp();
+        assert!(now >= case.commit_deadline, "Commit phase still open");
+        assert!(now < case.reveal_deadline, "Reveal phase has ended");
+
+        let commitment: BytesN<32> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::JuryCommit(job_id, juror.clone()))
+            .expect("No committed vote");
+        assert!(
+            Self::vote_commitment(&env, verdict, &salt) == commitment,
+            "Vote does not match commitment"
+        );
+
+        let key = DataKey::JuryVote(job_id, juror.clone());
+        assert!(!env.storage().persistent().has(&key), "Vote already revealed");
+        env.storage().persistent().set(&key, &verdict);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "vote_revealed"), job_id),
+            (juror, verdict),
+        );
+    }
+
+    /// Tally revealed votes, reward and slash jurors and settle the dispute
+    ///
+    /// Anyone may call this once the reveal phase is over. A verdict needs a
+    /// majority of the whole panel; without one the dispute returns to the arbiter.
+    ///
+    /// # Arguments
+    /// * `job_id` - ID of the disputed job
+    pub fn finalize_jury(env: Env, job_id: u64) {
+        Self::require_not_paused(&env);
+
+        let mut case = Self::get_jury_case(env.clone(), job_id);
+        assert!(case.outcome == JuryOutcome::Voting, "Jury already finalized");
+        assert!(
+            env.ledger().timestamp() >= case.reveal_deadline,
+            "Reveal phase still open"
+        );
+
+        // Tally revealed votes
+        let mut hirer_votes = 0u32;
+        let mut agent_votes = 0u32;
+        for juror in case.panel.iter() {
+            let vote: Option<Verdict> = env
+                .storage()
+                .persistent()
+                .get(&DataKey::JuryVote(job_id, juror));
+            match vote {
+                Some(Verdict::Hirer) => hirer_votes += 1,
+                Some(Verdict::Agent) => agent_votes += 1,
+                None => {}
+            }
+        }
+        let threshold = case.panel.len() / 2 + 1;
+        let verdict = if hirer_votes >= threshold {
+            Some(Verdict::Hirer)
+        } else if agent_votes >= threshold {
+            Some(Verdict::Agent)
+        } else {
+            None
+        };
+
+        // Jurors who revealed with the verdict (or at all, when there is none) are
+        // rewarded; everyone else is slashed
+        let config = Self::get_jury_config(env.clone());
+        let mut rewarded = Vec::new(&env);
+        let mut slashed = Vec::new(&env);
+        for juror in case.panel.iter() {
+            let vote: Option<Verdict> = env
+                .storage()
+                .persistent()
+                .get(&DataKey::JuryVote(job_id, juror.clone()));
+            let in_majority = match (vote, verdict) {
+                (Some(vote), Some(verdict)) => vote == verdict,
+                (Some(_), None) => true,
+                (None, _) => false,
+            };
+            if in_majority {
+                rewarded.push_back(juror.clone());
+            } else {
+                slashed.push_back(juror.clone());
+            }
+
+            let key = DataKey::JurorActiveCases(juror);
+            let active_cases: u32 = env.storage().persistent().get(&key).unwrap_or(0);
+            env.storage()
+                .persistent()
+                .set(&key, &active_cases.saturating_sub(1));
+        }
+        if !rewarded.is_empty() {
+            Self::redistribute_stakes(&env, &config, &slashed, &rewarded);
+        }
+
+        case.outcome = match verdict {
+            Some(Verdict::Hirer) => JuryOutcome::Hirer,
+            Some(Verdict::Agent) => JuryOutcome::Agent,
+            None => JuryOutcome::NoMajority,
+        };
+        env.storage().persistent().set(&DataKey::JuryCase(job_id), &case);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "jury_finalized"), job_id),
+            (hirer_votes, agent_votes, case.outcome),
+        );
+
+        if let Some(verdict) = verdict {
+            let mut job = Self::load_job(&env, job_id);
+            let hirer_amount = match verdict {
+                Verdict::Hirer => job.amount,
+                Verdict::Agent => 0,
+            };
+            Self::settle_dispute(&env, &mut job, hirer_amount);
+        }
+    }
+
+    /// Get the jury settings
+    pub fn get_jury_config(env: Env) -> JuryConfig {
+        Self::try_get_jury_config(&env).expect("Jury not configured")
+    }
+
+    /// Get the jury case for a disputed job
+    pub fn get_jury_case(env: Env, job_id: u64) -> JuryCase {
+        env.storage()
+            .persistent()
+            .get(&DataKey::JuryCase(job_id))
+            .expect("Jury case not found")
+    }
+
+    /// Get a juror's current stake
+    pub fn get_juror_stake(env: Env, juror: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::JurorStake(juror))
+            .unwrap_or(0)
+    }
+
+    /// Get all jurors eligible to be drawn
+    pub fn get_jurors(env: Env) -> Vec<Address> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Jurors)
+            .unwrap_or(Vec::new(&env))
+    }
+}
+
+impl AgentEscrowContract {
+    /// Whether a jury is currently deciding the dispute on a job
+    pub(crate) fn has_open_jury_case(env: &Env, job_id: u64) -> bool {
+        let case: Option<JuryCase> = env.storage().persistent().get(&DataKey::JuryCase(job_id));
+        case.is_some_and(|case| case.outcome == JuryOutcome::Voting)
+    }
+
+    /// Check jury settings before they are scheduled or applied
+    pub(crate) fn validate_jury_config(env: &Env, config: &JuryConfig) {
+        assert!(config.min_stake > 0, "Minimum stake must be positive");
+        assert!(config.panel_size > 0, "Panel size must be positive");
+        assert!(config.slash_bps <= 10_000, "Invalid slash rate");
+        if let Some(current) = Self::try_get_jury_config(env) {
+            assert!(
+                current.stake_token == config.stake_token,
+                "Jury stake token cannot change"
+            );
+        }
+    }
+
+    fn try_get_jury_config(env: &Env) -> Option<JuryConfig> {
+        env.storage().persistent().get(&DataKey::JuryConfig)
+    }
+
+    fn vote_commitment(env: &Env, verdict: Verdict, salt: &BytesN<32>) -> BytesN<32> {
+        let mut preimage = Bytes::new(env);
+        preimage.push_back(match verdict {
+            Verdict::Hirer => 0,
+            Verdict::Agent => 1,
+        });
+        preimage.append(&Bytes::from(salt));
+        env.crypto().sha256(&preimage).into()
+    }
+
+    fn redistribute_stakes(
+        env: &Env,
+        config: &JuryConfig,
+        slashed: &Vec<Address>,
+        rewarded: &Vec<Address>,
+    ) {
+        let mut total_slashed = 0i128;
+        for juror in slashed.iter() {
+            let stake = Self::get_juror_stake(env.clone(), juror.clone());
+            let penalty = stake * config.slash_bps as i128 / 10_000;
+            total_slashed += penalty;
+            env.storage()
+                .persistent()
+                .set(&DataKey::JurorStake(juror.clone()), &(stake - penalty));
+            if stake - penalty < config.min_stake {
+                Self::remove_from_juror_pool(env, &juror);
+            }
+        }
+
+        // Share slashed stake equally; the first juror also takes the remainder
+        let share = total_slashed / rewarded.len() as i128;
+        let remainder = total_slashed - share * rewarded.len() as i128;
+        for (i, juror) in rewarded.iter().enumerate() {
+            let reward = if i == 0 { share + remainder } else { share };
+            let stake = Self::get_juror_stake(env.clone(), juror.clone()) + reward;
+            env.storage()
+                .persistent()
+                .set(&DataKey::JurorStake(juror.clone()), &stake);
+            if stake >= config.min_stake {
+                Self::add_to_juror_pool(env, &juror);
+            }
+        }
+    }
+
+    fn add_to_juror_pool(env: &Env, juror: &Address) {
+        let mut jurors = Self::get_jurors(env.clone());
+        if !jurors.contains(juror) {
+            jurors.push_back(juror.clone());
+            env.storage().persistent().set(&DataKey::Jurors, &jurors);
+        }
+    }
+
+    fn remove_from_juror_pool(env: &Env, juror: &Address) {
+        let mut jurors = Self::get_jurors(env.clone());
+        if let Some(index) = jurors.first_index_of(juror) {
+            jurors.remove(index);
+            env.storage().persistent().set(&DataKey::Jurors, &jurors);
+        }
+    }
+}
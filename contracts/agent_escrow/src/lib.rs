@@ -4,6 +4,25 @@ use soroban_sdk::{
     contract, contractimpl, contracttype, token, Address, Env, String, Symbol, Vec, BytesN,
 };
 
+pub mod allowance;
+pub mod auction;
+pub mod jury;
+pub mod metering;
+pub mod migration;
+pub mod oracle;
+pub mod pipeline;
+pub mod pool;
+pub mod quote;
+pub mod registry;
+pub mod streaming;
+pub mod subcontract;
+pub mod tasks;
+pub mod timelock;
+pub mod verifier;
+pub mod tokens;
+
+use registry::RegistryClient;
+
 /// Job status enumeration
 #[contracttype]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -12,6 +31,59 @@ pub enum JobStatus {
     Completed = 1,
     Cancelled = 2,
     Disputed = 3,
+    Released = 4,
+    Resolved = 5,
+    Locked = 6,
+    Funding = 7,
+}
+
+/// Usage-based pricing for a metered job
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Metering {
+    pub unit_price: i128,
+    pub units: Option<u64>,
+}
+
+/// Time window over which a streamed job pays out
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Stream {
+    pub start: u64,
+    pub end: u64,
+    pub withdrawn: i128,
+}
+
+/// Position of a job in a pipeline
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Stage {
+    pub pipeline_id: u64,
+    pub index: u32,
+}
+
+/// Funding round of a crowdfunded job
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pool {
+    pub deadline: u64,
+    pub raised: i128,
+}
+
+/// How a job's payout is determined
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum JobKind {
+    /// The full amount is paid
+    Fixed,
+    /// Reported usage is paid, capped at the amount; the rest is refunded
+    Metered(Metering),
+    /// The amount accrues to the agent owner linearly over a time window
+    Streamed(Stream),
+    /// Pipeline stage, locked until the previous stage completes
+    Staged(Stage),
+    /// Crowdfunded up to the amount by several contributors; refunds are split pro-rata
+    Pooled(Pool),
 }
 
 /// Job data structure
@@ -23,10 +95,45 @@ pub struct Job {
     pub agent_owner: Address,
     pub agent_id: String,
     pub amount: i128,
+    pub token: Address,
     pub status: JobStatus,
     pub created_at: u64,
     pub completed_at: Option<u64>,
+    pub release_at: Option<u64>,
     pub results_hash: Option<BytesN<32>>,
+    pub kind: JobKind,
+}
+
+/// Open dispute on a job
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Dispute {
+    pub initiator: Address,
+    pub opened_at: u64,
+    pub response_deadline: u64,
+}
+
+/// Evidence submitted by a party to a dispute
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Evidence {
+    pub submitter: Address,
+    pub evidence_hash: BytesN<32>,
+    pub uri: String,
+    pub submitted_at: u64,
+}
+
+/// Escrow liabilities for a token compared with the contract's actual balance
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Solvency {
+    pub escrowed: i128,
+    pub claimable: i128,
+    pub fees: i128,
+    pub staked: i128,
+    pub liabilities: i128,
+    pub balance: i128,
+    pub is_solvent: bool,
 }
 
 /// Storage keys
@@ -36,6 +143,74 @@ pub enum DataKey {
     Job(u64),
     JobsByHirer(Address),
     JobsByOwner(Address),
+    Registry,
+    OpenJobs(String),
+    JobsByAgent(String),
+    JobsByAgentStatus(String, JobStatus),
+    JobByRef(Address, BytesN<32>),
+    Claimable(Address, Address),
+    Admin,
+    Arbiter,
+    ChallengePeriod,
+    ResponsePeriod,
+    Dispute(u64),
+    Evidence(u64),
+    JuryConfig,
+    Jurors,
+    JurorStake(Address),
+    JurorActiveCases(Address),
+    JuryCase(u64),
+    JuryCommit(u64, Address),
+    JuryVote(u64, Address),
+    Paused,
+    FeeBps,
+    FeeBalance(Address),
+    MinDelay,
+    OpCounter,
+    ScheduledOp(u64),
+    PendingOps,
+    TotalEscrowed(Address),
+    TotalClaimable(Address),
+    TotalStaked(Address),
+    Allowance(Address, Address, Address),
+    QuoteNonce(String, u64),
+    PriceOracle,
+    SupportedTokens,
+    TokenMinAmount(Address),
+    TaskCounter,
+    Task(u64),
+    OpenTasks,
+    TaskClaims(u64),
+    Bid(u64, String),
+    PipelineCounter,
+    Pipeline(u64),
+    ParentJob(u64),
+    ChildJobs(u64),
+    Contributions(u64),
+    JobVerifier(u64),
+    JobInputs(u64),
+}
+
+/// Default time completed payouts are held open to disputes (24 hours)
+const DEFAULT_CHALLENGE_PERIOD: u64 = 86_400;
+
+/// Default time the other party has to answer a dispute (72 hours)
+const DEFAULT_RESPONSE_PERIOD: u64 = 259_200;
+
+/// Default minimum delay before a scheduled admin operation can run (48 hours)
+pub(crate) const DEFAULT_MIN_DELAY: u64 = 172_800;
+
+/// Highest platform fee the admin can set (10%)
+pub(crate) const MAX_FEE_BPS: u32 = 1_000;
+
+/// Where the funds for a new job come from
+pub(crate) enum Funding {
+    /// Transferred from the hirer
+    Hirer,
+    /// Pulled from the hirer under a spender's allowance
+    Spender(Address),
+    /// Already held by the escrow, e.g. a task reward
+    Escrowed,
 }
 
 #[contract]
@@ -44,143 +219,218 @@ pub struct AgentEscrowContract;
 #[contractimpl]
 impl AgentEscrowContract {
     /// Initialize the contract (sets job counter to 0)
-    pub fn initialize(env: Env) {
+    /// 
+    /// # Arguments
+    /// * `admin` - Address allowed to schedule setting changes
+    /// * `registry` - Address of the agent registry contract used for agent limits
+    /// * `arbiter` - Address that resolves disputes until the admin changes it
+    /// * `fee_bps` - Platform fee taken from agent payouts, in basis points
+    pub fn initialize(env: Env, admin: Address, registry: Address, arbiter: Address, fee_bps: u32) {
+        assert!(
+            !env.storage().persistent().has(&DataKey::Admin),
+            "Already initialized"
+        );
+        assert!(fee_bps <= MAX_FEE_BPS, "Fee too high");
+
+        env.storage().persistent().set(&DataKey::Admin, &admin);
+        env.storage().persistent().set(&DataKey::Arbiter, &arbiter);
+        env.storage().persistent().set(&DataKey::Registry, &registry);
+        env.storage()
+            .persistent()
+            .set(&DataKey::ChallengePeriod, &DEFAULT_CHALLENGE_PERIOD);
+        env.storage()
+            .persistent()
+            .set(&DataKey::ResponsePeriod, &DEFAULT_RESPONSE_PERIOD);
+        env.storage().persistent().set(&DataKey::MinDelay, &DEFAULT_MIN_DELAY);
+        env.storage().persistent().set(&DataKey::FeeBps, &fee_bps);
         env.storage().persistent().set(&DataKey::JobCounter, &0u64);
     }
 
+    /// Pause state-changing entry points
+    /// 
+    /// Refund and exit paths (`cancel_job`, `withdraw`, `leave_jury`) stay
+    /// available so funds are never trapped.
+    pub fn pause(env: Env) {
+        let admin = Self::get_admin(env.clone());
+        admin.require_auth();
+
+        env.storage().persistent().set(&DataKey::Paused, &true);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "contract_paused"), admin),
+            (),
+        );
+    }
+
+    /// Resume normal operation after a pause
+    pub fn unpause(env: Env) {
+        let admin = Self::get_admin(env.clone());
+        admin.require_auth();
+
+        env.storage().persistent().set(&DataKey::Paused, &false);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "contract_unpaused"), admin),
+            (),
+        );
+    }
+
+    /// Whether the contract is paused
+    pub fn is_paused(env: Env) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Paused)
+            .unwrap_or(false)
+    }
+
     /// Create a new escrow job
     /// 
     /// # Arguments
     /// * `hirer` - Address of the person hiring the agent
-    /// * `agent_owner` - Address of the agent owner who will receive payment
-    /// * `agent_id` - Unique identifier of the agent being hired
-    /// * `amount` - Payment amount in stroops (1 XLM = 10,000,000 stroops)
+    /// * `agent_id` - Unique identifier of the agent being hired; paid to its registry owner
+    /// * `amount` - Payment amount in stroops (1 XLM = 10,000,000 stroops); for agents
+    ///   priced in another currency, the most the hirer will pay after conversion
     /// * `token` - Token contract address for payment (use native token for XLM)
+    /// * `client_ref` - Optional idempotency key; repeating it returns the existing job ID
+    /// * `inputs_hash` - Optional hash of the inputs given to the agent, checked by its verifier
+    /// * `expected_verifier` - Verifier the hirer accepts for `complete_with_verifier`; must be
+    ///   the agent's current one, and `None` leaves the job without a verifier
+    #[allow(clippy::too_many_arguments)]
     pub fn create_job(
         env: Env,
         hirer: Address,
-        agent_owner: Address,
         agent_id: String,
         amount: i128,
         token: Address,
+        client_ref: Option<BytesN<32>>,
+        inputs_hash: Option<BytesN<32>>,
+        expected_verifier: Option<Address>,
     ) -> u64 {
+        Self::require_not_paused(&env);
+
         // Verify the hirer is the caller
         hirer.require_auth();
 
-        // Get and increment job counter
-        let mut counter: u64 = env
-            .storage()
-            .persistent()
-            .get(&DataKey::JobCounter)
-            .unwrap_or(0);
-        
-        counter += 1;
-        let job_id = counter;
-
-        // Transfer tokens from hirer to contract (escrow)
-        let client = token::Client::new(&env, &token);
-        client.transfer(&hirer, &env.current_contract_address(), &amount);
+        // Retries return the existing job before the price is converted again
+        if let Some(job_id) = Self::existing_job(&env, &hirer, &client_ref) {
+            return job_id;
+        }
 
-        // Create job record
-        let job = Job {
-            id: job_id,
-            hirer: hirer.clone(),
-            agent_owner: agent_owner.clone(),
-            agent_id: agent_id.clone(),
+        let amount = Self::job_amount(&env, &agent_id, &token, amount);
+        let agent = Self::registry_client(&env).get_agent(&agent_id);
+        let job_id = Self::open_job(
+            &env,
+            hirer,
+            agent.owner,
+            agent_id.clone(),
             amount,
-            status: JobStatus::Pending,
-            created_at: env.ledger().timestamp(),
-            completed_at: None,
-            results_hash: None,
-        };
-
-        // Store job
-        env.storage().persistent().set(&DataKey::Job(job_id), &job);
-        env.storage().persistent().set(&DataKey::JobCounter, &counter);
-
-        // Update indexes
-        Self::add_to_hirer_jobs(&env, &hirer, job_id);
-        Self::add_to_owner_jobs(&env, &agent_owner, job_id);
-
-        // Emit event
-        env.events().publish(
-            (Symbol::new(&env, "job_created"), job_id),
-            (hirer, agent_owner, agent_id, amount),
+            token,
+            client_ref,
+            Funding::Hirer,
+            JobKind::Fixed,
         );
+        Self::record_verification(&env, job_id, &agent_id, inputs_hash, expected_verifier);
 
         job_id
     }
 
-    /// Complete a job and release payment to agent owner
+
+    /// Complete a job and hold payment for the challenge window
+    /// 
+    /// The payout is credited to the agent owner by `finalize` once the
+    /// window has passed without a dispute.
     /// 
     /// # Arguments
     /// * `job_id` - ID of the job to complete
     /// * `results_hash` - Hash of the execution results for verification
-    /// * `token` - Token contract address
+    /// * `token` - Token contract address (must match the job's token)
     pub fn complete_job(
         env: Env,
         job_id: u64,
         results_hash: BytesN<32>,
         token: Address,
     ) {
-        let mut job: Job = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Job(job_id))
-            .expect("Job not found");
+        Self::require_not_paused(&env);
+
+        let mut job = Self::load_job(&env, job_id);
 
         // Only the hirer can mark job as complete
         job.hirer.require_auth();
 
         // Verify job is still pending
         assert!(job.status == JobStatus::Pending, "Job is not pending");
+        assert!(job.token == token, "Token mismatch");
+        Self::require_children_settled(&env, job_id);
+        Self::require_parent_approval(&env, &job);
 
-        // Update job status
-        job.status = JobStatus::Completed;
-        job.completed_at = Some(env.ledger().timestamp());
-        job.results_hash = Some(results_hash.clone());
+        Self::mark_completed(&env, &mut job, results_hash);
+    }
 
-        // Transfer payment to agent owner
-        let client = token::Client::new(&env, &token);
-        client.transfer(&env.current_contract_address(), &job.agent_owner, &job.amount);
+    /// Release a completed job's held payment once its challenge window has passed
+    /// 
+    /// Anyone may call this, so payouts never depend on the hirer.
+    /// 
+    /// # Arguments
+    /// * `job_id` - ID of the completed job
+    pub fn finalize(env: Env, job_id: u64) {
+        Self::require_not_paused(&env);
 
-        // Save updated job
-        env.storage().persistent().set(&DataKey::Job(job_id), &job);
+        let mut job = Self::load_job(&env, job_id);
 
-        // Emit event
-        env.events().publish(
-            (Symbol::new(&env, "job_completed"), job_id),
-            (job.agent_owner, job.amount, results_hash),
+        assert!(job.status == JobStatus::Completed, "Job is not completed");
+        assert!(
+            env.ledger().timestamp() >= job.release_at.unwrap(),
+            "Challenge window still open"
         );
+        Self::require_parent_not_disputed(&env, &job);
+
+        Self::release_payment(&env, &mut job);
     }
 
     /// Cancel a job and refund the hirer
     /// 
+    /// Contributors to a crowdfunded job are credited their pro-rata shares instead.
+    /// 
     /// # Arguments
     /// * `job_id` - ID of the job to cancel
-    /// * `token` - Token contract address
+    /// * `token` - Token contract address (must match the job's token)
     pub fn cancel_job(env: Env, job_id: u64, token: Address) {
-        let mut job: Job = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Job(job_id))
-            .expect("Job not found");
+        let mut job = Self::load_job(&env, job_id);
 
         // Only the hirer can cancel
         job.hirer.require_auth();
 
         // Verify job is still pending
         assert!(job.status == JobStatus::Pending, "Job is not pending");
+        assert!(job.token == token, "Token mismatch");
+        assert!(
+            !matches!(job.kind, JobKind::Streamed(_)),
+            "Streamed jobs settle through the stream"
+        );
+        Self::require_children_settled(&env, job_id);
 
         // Update job status
-        job.status = JobStatus::Cancelled;
+        Self::set_status(&env, &mut job, JobStatus::Cancelled);
 
-        // Refund hirer
-        let client = token::Client::new(&env, &token);
-        client.transfer(&env.current_contract_address(), &job.hirer, &job.amount);
+        // Refund hirer; a child job's funds go back to its parent instead
+        Self::adjust_total(&env, DataKey::TotalEscrowed(token.clone()), -job.amount);
+        if !Self::return_to_parent(&env, &job, job.amount) {
+            if matches!(job.kind, JobKind::Pooled(_)) {
+                // Contributors withdraw their shares, so no one transfer can block the rest
+                for (funder, share) in Self::refund_shares(&env, &job, job.amount) {
+                    Self::credit_claimable(&env, &funder, &token, share);
+                }
+            } else {
+                let client = token::Client::new(&env, &token);
+                client.transfer(&env.current_contract_address(), &job.hirer, &job.amount);
+            }
+        }
 
         // Save updated job
-        env.storage().persistent().set(&DataKey::Job(job_id), &job);
+        Self::save_job(&env, &job);
+        Self::release_open_job(&env, &job.agent_id);
 
         // Emit event
         env.events().publish(
@@ -189,44 +439,273 @@ impl AgentEscrowContract {
         );
     }
 
+    /// Withdraw funds credited to an address (agent earnings or dispute refunds)
+    /// 
+    /// # Arguments
+    /// * `owner` - Address whose claimable balance is withdrawn
+    /// * `token` - Token contract address
+    /// * `amount` - Amount to withdraw
+    pub fn withdraw(env: Env, owner: Address, token: Address, amount: i128) {
+        // Only the balance holder can withdraw
+        owner.require_auth();
+
+        assert!(amount > 0, "Amount must be positive");
+        let claimable = Self::get_claimable(env.clone(), owner.clone(), token.clone());
+        assert!(amount <= claimable, "Insufficient claimable balance");
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Claimable(owner.clone(), token.clone()), &(claimable - amount));
+
+        // Transfer tokens to owner
+        let client = token::Client::new(&env, &token);
+        client.transfer(&env.current_contract_address(), &owner, &amount);
+        Self::adjust_total(&env, DataKey::TotalClaimable(token.clone()), -amount);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "earnings_withdrawn"), owner),
+            (token, amount),
+        );
+    }
+
+    /// Withdraw accumulated platform fees
+    /// 
+    /// # Arguments
+    /// * `to` - Address receiving the fees
+    /// * `token` - Token contract address
+    /// * `amount` - Amount to withdraw
+    pub fn withdraw_fees(env: Env, to: Address, token: Address, amount: i128) {
+        Self::get_admin(env.clone()).require_auth();
+
+        assert!(amount > 0, "Amount must be positive");
+        let balance = Self::get_fee_balance(env.clone(), token.clone());
+        assert!(amount <= balance, "Insufficient fee balance");
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::FeeBalance(token.clone()), &(balance - amount));
+
+        let client = token::Client::new(&env, &token);
+        client.transfer(&env.current_contract_address(), &to, &amount);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "fees_withdrawn"), to),
+            (token, amount),
+        );
+    }
+
     /// Initiate a dispute for a job
     /// 
     /// # Arguments
     /// * `job_id` - ID of the job to dispute
     /// * `caller` - Address of the party initiating the dispute
     pub fn dispute_job(env: Env, caller: Address, job_id: u64) {
+        Self::require_not_paused(&env);
+
         // Verify caller authorization
         caller.require_auth();
 
-        let mut job: Job = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Job(job_id))
-            .expect("Job not found");
+        let mut job = Self::load_job(&env, job_id);
 
-        // Verify caller is either hirer or agent owner
+        // Verify caller is either hirer or acts for the agent
         assert!(
-            caller == job.hirer || caller == job.agent_owner,
+            caller == job.hirer || Self::is_agent_operator(&env, &job.agent_id, &caller),
             "Only hirer or agent owner can dispute"
         );
 
         // Verify job is pending or completed
         assert!(
             job.status == JobStatus::Pending || job.status == JobStatus::Completed,
-            "Cannot dispute cancelled job"
+            "Job cannot be disputed"
+        );
+        // Either party can stop a stream instead, and part of it may be paid out
+        assert!(
+            !matches!(job.kind, JobKind::Streamed(_)),
+            "Streamed jobs cannot be disputed"
         );
 
+        // Completed payouts can only be challenged while they are still held
+        if job.status == JobStatus::Completed {
+            assert!(
+                env.ledger().timestamp() < job.release_at.unwrap(),
+                "Challenge window has closed"
+            );
+        }
+
         // Update job status
-        job.status = JobStatus::Disputed;
+        Self::set_status(&env, &mut job, JobStatus::Disputed);
+
+        // Subcontracted work that has not been completed is called back
+        Self::reclaim_pending_children(&env, &mut job);
+
+        // Open the dispute; the other party must respond before the deadline
+        let response_period: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::ResponsePeriod)
+            .unwrap_or(DEFAULT_RESPONSE_PERIOD);
+        let now = env.ledger().timestamp();
+        let dispute = Dispute {
+            initiator: caller.clone(),
+            opened_at: now,
+            response_deadline: now + response_period,
+        };
 
         // Save updated job
-        env.storage().persistent().set(&DataKey::Job(job_id), &job);
+        Self::save_job(&env, &job);
+        env.storage().persistent().set(&DataKey::Dispute(job_id), &dispute);
 
         // Emit event
         env.events().publish(
             (Symbol::new(&env, "dispute_initiated"), job_id),
-            (),
+            (caller, dispute.response_deadline),
+        );
+    }
+
+    /// Attach evidence to an open dispute
+    /// 
+    /// Evidence is append-only; earlier submissions cannot be changed.
+    /// 
+    /// # Arguments
+    /// * `caller` - Hirer or agent owner of the disputed job
+    /// * `job_id` - ID of the disputed job
+    /// * `evidence_hash` - Hash of the evidence content
+    /// * `uri` - Location of the evidence (e.g., IPFS)
+    pub fn submit_evidence(
+        env: Env,
+        caller: Address,
+        job_id: u64,
+        evidence_hash: BytesN<32>,
+        uri: String,
+    ) {
+        Self::require_not_paused(&env);
+
+        // Verify caller authorization
+        caller.require_auth();
+
+        let job = Self::load_job(&env, job_id);
+
+        assert!(
+            caller == job.hirer || Self::is_agent_operator(&env, &job.agent_id, &caller),
+            "Only hirer or agent owner can submit evidence"
+        );
+        assert!(job.status == JobStatus::Disputed, "Job is not disputed");
+
+        let mut evidence = Self::get_evidence(env.clone(), job_id);
+        evidence.push_back(Evidence {
+            submitter: caller.clone(),
+            evidence_hash: evidence_hash.clone(),
+            uri,
+            submitted_at: env.ledger().timestamp(),
+        });
+        env.storage()
+            .persistent()
+            .set(&DataKey::Evidence(job_id), &evidence);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "evidence_submitted"), job_id),
+            (caller, evidence_hash),
+        );
+    }
+
+    /// Settle a dispute in the caller's favour when the other party never responded
+    /// 
+    /// The initiator took part by opening the dispute; the other party takes
+    /// part by submitting evidence before the response deadline. Once the
+    /// deadline has passed, a party that took part wins if the other side
+    /// submitted nothing in time. Later evidence is kept for the arbiter but
+    /// does not count as a response.
+    /// 
+    /// # Arguments
+    /// * `caller` - Hirer or agent owner of the disputed job
+    /// * `job_id` - ID of the disputed job
+    pub fn claim_default(env: Env, caller: Address, job_id: u64) {
+        Self::require_not_paused(&env);
+
+        // Verify caller authorization
+        caller.require_auth();
+
+        let mut job = Self::load_job(&env, job_id);
+        assert!(job.status == JobStatus::Disputed, "Job is not disputed");
+
+        assert!(!Self::has_open_jury_case(&env, job_id), "Dispute is before a jury");
+
+        // Both sides of a child job may be the same person
+        assert!(
+            Self::get_parent_job(env.clone(), job_id).is_none(),
+            "Child job disputes need the arbiter"
+        );
+
+        let caller_is_hirer = caller == job.hirer;
+        assert!(
+            caller_is_hirer || Self::is_agent_operator(&env, &job.agent_id, &caller),
+            "Only hirer or agent owner can claim"
         );
+        let dispute = Self::get_dispute(env.clone(), job_id);
+        assert!(
+            env.ledger().timestamp() >= dispute.response_deadline,
+            "Response deadline not reached"
+        );
+
+        // Any timely submission from the other side means the arbiter must decide
+        let evidence = Self::get_evidence(env.clone(), job_id);
+        // An organization agent's side may be several members
+        let on_callers_side = |party: &Address| (*party == job.hirer) == caller_is_hirer;
+        let mut responded = on_callers_side(&dispute.initiator);
+        for item in evidence.iter() {
+            if item.submitted_at >= dispute.response_deadline {
+                continue;
+            }
+            assert!(on_callers_side(&item.submitter), "Other party has responded");
+            responded = true;
+        }
+        assert!(responded, "Caller has not responded");
+
+        let hirer_amount = if caller_is_hirer { job.amount } else { 0 };
+        Self::settle_dispute(&env, &mut job, hirer_amount);
+    }
+
+    /// Resolve a disputed job by splitting its escrowed funds
+    /// 
+    /// Both shares are credited to claimable balances.
+    /// 
+    /// # Arguments
+    /// * `job_id` - ID of the disputed job
+    /// * `hirer_amount` - Portion refunded to the hirer; the rest goes to the agent owner
+    pub fn resolve_dispute(env: Env, job_id: u64, hirer_amount: i128) {
+        Self::require_not_paused(&env);
+
+        Self::get_arbiter(env.clone()).require_auth();
+
+        let mut job = Self::load_job(&env, job_id);
+
+        assert!(job.status == JobStatus::Disputed, "Job is not disputed");
+        assert!(!Self::has_open_jury_case(&env, job_id), "Dispute is before a jury");
+        assert!(
+            hirer_amount >= 0 && hirer_amount <= job.amount,
+            "Invalid hirer amount"
+        );
+
+        Self::settle_dispute(&env, &mut job, hirer_amount);
+    }
+
+    /// Get the contract admin
+    pub fn get_admin(env: Env) -> Address {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Admin)
+            .expect("Not initialized")
+    }
+
+    /// Get the address that resolves disputes
+    pub fn get_arbiter(env: Env) -> Address {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Arbiter)
+            .expect("Not initialized")
     }
 
     /// Get job details
@@ -234,10 +713,85 @@ impl AgentEscrowContract {
     /// # Arguments
     /// * `job_id` - ID of the job to retrieve
     pub fn get_job(env: Env, job_id: u64) -> Job {
+        Self::load_job(&env, job_id)
+    }
+
+    /// Look up a job by the hirer's client reference key
+    /// 
+    /// # Arguments
+    /// * `hirer` - Address of the hirer who created the job
+    /// * `client_ref` - Reference key passed to `create_job`
+    pub fn get_job_by_ref(env: Env, hirer: Address, client_ref: BytesN<32>) -> Option<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::JobByRef(hirer, client_ref))
+    }
+
+    /// Get the balance an address can withdraw for a token
+    pub fn get_claimable(env: Env, owner: Address, token: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Claimable(owner, token))
+            .unwrap_or(0)
+    }
+
+    /// Get the platform fee taken from agent payouts, in basis points
+    pub fn get_fee_bps(env: Env) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::FeeBps)
+            .unwrap_or(0)
+    }
+
+    /// Get platform fees collected for a token and not yet withdrawn
+    pub fn get_fee_balance(env: Env, token: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::FeeBalance(token))
+            .unwrap_or(0)
+    }
+
+    /// Compare tracked liabilities for a token with the tokens the contract holds
+    /// 
+    /// Liabilities are funds locked in jobs, balances credited for withdrawal,
+    /// uncollected platform fees and juror stakes. The contract is solvent when
+    /// its token balance covers all of them.
+    /// 
+    /// # Arguments
+    /// * `token` - Token contract address
+    pub fn get_solvency(env: Env, token: Address) -> Solvency {
+        let escrowed = Self::get_total(&env, &DataKey::TotalEscrowed(token.clone()));
+        let claimable = Self::get_total(&env, &DataKey::TotalClaimable(token.clone()));
+        let fees = Self::get_fee_balance(env.clone(), token.clone());
+        let staked = Self::get_total(&env, &DataKey::TotalStaked(token.clone()));
+        let liabilities = escrowed + claimable + fees + staked;
+        let balance = token::Client::new(&env, &token).balance(&env.current_contract_address());
+
+        Solvency {
+            escrowed,
+            claimable,
+            fees,
+            staked,
+            liabilities,
+            balance,
+            is_solvent: balance >= liabilities,
+        }
+    }
+
+    /// Get the dispute opened on a job
+    pub fn get_dispute(env: Env, job_id: u64) -> Dispute {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Dispute(job_id))
+            .expect("Dispute not found")
+    }
+
+    /// Get all evidence submitted for a job's dispute, oldest first
+    pub fn get_evidence(env: Env, job_id: u64) -> Vec<Evidence> {
         env.storage()
             .persistent()
-            .get(&DataKey::Job(job_id))
-            .expect("Job not found")
+            .get(&DataKey::Evidence(job_id))
+            .unwrap_or(Vec::new(&env))
     }
 
     /// Get all jobs for a hirer
@@ -256,8 +810,323 @@ impl AgentEscrowContract {
             .unwrap_or(Vec::new(&env))
     }
 
+    /// Get a page of jobs for an agent, optionally filtered by status
+    /// 
+    /// # Arguments
+    /// * `agent_id` - ID of the agent
+    /// * `status` - Only return jobs currently in this status (`None` = all jobs)
+    /// * `offset` - Number of matching jobs to skip
+    /// * `limit` - Maximum number of job IDs to return
+    pub fn get_jobs_by_agent(
+        env: Env,
+        agent_id: String,
+        status: Option<JobStatus>,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<u64> {
+        let key = match status {
+            Some(status) => DataKey::JobsByAgentStatus(agent_id, status),
+            None => DataKey::JobsByAgent(agent_id),
+        };
+        let jobs: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(Vec::new(&env));
+
+        let start = offset.min(jobs.len());
+        let end = offset.saturating_add(limit).min(jobs.len());
+        jobs.slice(start..end)
+    }
+
+    /// Get the number of accepted but unsettled jobs for an agent
+    pub fn get_open_jobs(env: Env, agent_id: String) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::OpenJobs(agent_id))
+            .unwrap_or(0)
+    }
+
     // Internal helper functions
 
+    fn require_not_paused(env: &Env) {
+        assert!(!Self::is_paused(env.clone()), "Contract is paused");
+    }
+
+    /// Job already created under a hirer's idempotency key, if any
+    fn existing_job(env: &Env, hirer: &Address, client_ref: &Option<BytesN<32>>) -> Option<u64> {
+        let client_ref = client_ref.clone()?;
+        Self::get_job_by_ref(env.clone(), hirer.clone(), client_ref)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn open_job(
+        env: &Env,
+        hirer: Address,
+        agent_owner: Address,
+        agent_id: String,
+        amount: i128,
+        token: Address,
+        client_ref: Option<BytesN<32>>,
+        funding: Funding,
+        kind: JobKind,
+    ) -> u64 {
+        // A retried call must not create and fund a second job
+        if let Some(job_id) = Self::existing_job(env, &hirer, &client_ref) {
+            return job_id;
+        }
+        assert!(amount > 0, "Amount must be positive");
+
+        // Only allowlisted tokens are trusted to behave during transfers
+        if !matches!(funding, Funding::Escrowed) {
+            Self::require_supported_token(env, &token, amount);
+        }
+
+        // Enforce the agent's concurrency limit from the registry
+        let agent = Self::registry_client(env).get_agent(&agent_id);
+        let open_jobs = Self::get_open_jobs(env.clone(), agent_id.clone());
+        assert!(
+            agent.max_open_jobs == 0 || open_jobs < agent.max_open_jobs,
+            "Agent is at capacity"
+        );
+
+        // Get and increment job counter
+        let mut counter: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::JobCounter)
+            .unwrap_or(0);
+        
+        counter += 1;
+        let job_id = counter;
+
+        // Transfer tokens from hirer to contract (escrow); spenders draw on the
+        // hirer's token approval for the escrow, limited by their own budget
+        if !matches!(funding, Funding::Escrowed) {
+            Self::adjust_total(env, DataKey::TotalEscrowed(token.clone()), amount);
+        }
+        let client = token::Client::new(env, &token);
+        match funding {
+            Funding::Hirer => client.transfer(&hirer, &env.current_contract_address(), &amount),
+            Funding::Spender(spender) => {
+                Self::spend_allowance(env, &hirer, &spender, &token, amount);
+                let escrow = env.current_contract_address();
+                client.transfer_from(&escrow, &hirer, &escrow, &amount);
+            }
+            Funding::Escrowed => {}
+        }
+
+        // Create job record
+        let job = Job {
+            id: job_id,
+            hirer: hirer.clone(),
+            agent_owner: agent_owner.clone(),
+            agent_id: agent_id.clone(),
+            amount,
+            token: token.clone(),
+            status: JobStatus::Pending,
+            created_at: env.ledger().timestamp(),
+            completed_at: None,
+            release_at: None,
+            results_hash: None,
+            kind,
+        };
+
+        // Store job
+        Self::save_job(env, &job);
+        env.storage().persistent().set(&DataKey::JobCounter, &counter);
+
+        // Update indexes
+        Self::add_to_hirer_jobs(env, &hirer, job_id);
+        Self::add_to_owner_jobs(env, &agent_owner, job_id);
+        Self::add_to_agent_jobs(env, &agent_id, job_id);
+        Self::add_to_status_jobs(env, &agent_id, JobStatus::Pending, job_id);
+        env.storage()
+            .persistent()
+            .set(&DataKey::OpenJobs(agent_id.clone()), &(open_jobs + 1));
+        if let Some(client_ref) = client_ref {
+            env.storage()
+                .persistent()
+                .set(&DataKey::JobByRef(hirer.clone(), client_ref), &job_id);
+        }
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(env, "job_created"), job_id),
+            (hirer, agent_owner, agent_id, amount),
+        );
+
+        job_id
+    }
+
+    pub(crate) fn registry_client(env: &Env) -> RegistryClient<'_> {
+        let registry: Address = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Registry)
+            .expect("Not initialized");
+        RegistryClient::new(env, &registry)
+    }
+
+    /// Whether an address acts for the agent side of a job
+    ///
+    /// Payouts go to `job.agent_owner`, which for an organization agent is
+    /// only a payout address; the registry decides who acts for the agent.
+    pub(crate) fn is_agent_operator(env: &Env, agent_id: &String, caller: &Address) -> bool {
+        Self::registry_client(env).is_agent_operator(agent_id, caller)
+    }
+
+    pub(crate) fn payout_amount(job: &Job) -> i128 {
+        match &job.kind {
+            JobKind::Fixed | JobKind::Staged(_) | JobKind::Pooled(_) => job.amount,
+            JobKind::Metered(metering) => {
+                let units = metering.units.expect("Usage not reported") as i128;
+                units.saturating_mul(metering.unit_price).min(job.amount)
+            }
+            JobKind::Streamed(_) => panic!("Streamed jobs settle through the stream"),
+        }
+    }
+
+    fn set_status(env: &Env, job: &mut Job, status: JobStatus) {
+        Self::remove_from_status_jobs(env, &job.agent_id, job.status, job.id);
+        Self::add_to_status_jobs(env, &job.agent_id, status, job.id);
+        job.status = status;
+    }
+
+    /// Record a pending job's results and start its challenge window
+    fn mark_completed(env: &Env, job: &mut Job, results_hash: BytesN<32>) {
+        let results_hash = match &job.kind {
+            JobKind::Metered(metering) => {
+                assert!(metering.units.is_some(), "Usage not reported");
+                results_hash
+            }
+            JobKind::Streamed(_) => panic!("Streamed jobs settle through the stream"),
+            // Each stage's hash commits to every stage before it
+            JobKind::Staged(stage) => Self::chain_stage_results(env, stage, results_hash),
+            JobKind::Fixed | JobKind::Pooled(_) => results_hash,
+        };
+
+        // Update job status
+        let challenge_period: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::ChallengePeriod)
+            .unwrap_or(DEFAULT_CHALLENGE_PERIOD);
+        let now = env.ledger().timestamp();
+        Self::set_status(env, job, JobStatus::Completed);
+        job.completed_at = Some(now);
+        job.release_at = Some(now + challenge_period);
+        job.results_hash = Some(results_hash.clone());
+
+        // Save updated job
+        Self::save_job(env, job);
+        Self::release_open_job(env, &job.agent_id);
+        if let JobKind::Staged(stage) = &job.kind {
+            Self::unlock_next_stage(env, stage);
+        }
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(env, "job_completed"), job.id),
+            (job.agent_owner.clone(), job.amount, results_hash),
+        );
+    }
+
+    /// Credit a completed job's payout to the agent owner
+    fn release_payment(env: &Env, job: &mut Job) {
+        Self::set_status(env, job, JobStatus::Released);
+        Self::adjust_total(env, DataKey::TotalEscrowed(job.token.clone()), -job.amount);
+
+        // Credit payment to agent owner; they withdraw it separately so a
+        // failing transfer to the recipient can never block settlement
+        let payout = Self::payout_amount(job);
+        Self::credit_payout(env, &job.agent_owner, &job.token, payout);
+
+        // Refund whatever metered usage did not consume
+        let refund = job.amount - payout;
+        if refund > 0 {
+            Self::credit_claimable(env, &job.hirer, &job.token, refund);
+        }
+
+        // Save updated job
+        Self::save_job(env, job);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(env, "payment_released"), job.id),
+            (job.agent_owner.clone(), payout),
+        );
+    }
+
+    fn settle_dispute(env: &Env, job: &mut Job, hirer_amount: i128) {
+        Self::require_no_open_children(env, job.id);
+
+        let owner_amount = job.amount - hirer_amount;
+        Self::set_status(env, job, JobStatus::Resolved);
+        Self::adjust_total(env, DataKey::TotalEscrowed(job.token.clone()), -job.amount);
+        if !Self::return_to_parent(env, job, hirer_amount) {
+            for (funder, share) in Self::refund_shares(env, job, hirer_amount) {
+                Self::credit_claimable(env, &funder, &job.token, share);
+            }
+        }
+        Self::credit_payout(env, &job.agent_owner, &job.token, owner_amount);
+
+        // Save updated job
+        Self::save_job(env, job);
+
+        // A job disputed before completion was still holding a capacity slot
+        if job.completed_at.is_none() {
+            Self::release_open_job(env, &job.agent_id);
+        }
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(env, "dispute_resolved"), job.id),
+            (hirer_amount, owner_amount),
+        );
+    }
+
+    fn credit_payout(env: &Env, owner: &Address, token: &Address, amount: i128) {
+        let net = Self::take_fee(env, token, amount);
+        Self::credit_claimable(env, owner, token, net);
+    }
+
+    /// Set aside the platform fee on a payout and return what is left
+    fn take_fee(env: &Env, token: &Address, amount: i128) -> i128 {
+        let fee = amount * Self::get_fee_bps(env.clone()) as i128 / 10_000;
+        if fee > 0 {
+            let balance = Self::get_fee_balance(env.clone(), token.clone());
+            env.storage()
+                .persistent()
+                .set(&DataKey::FeeBalance(token.clone()), &(balance + fee));
+        }
+        amount - fee
+    }
+
+    fn credit_claimable(env: &Env, owner: &Address, token: &Address, amount: i128) {
+        Self::adjust_total(env, DataKey::TotalClaimable(token.clone()), amount);
+        let claimable = Self::get_claimable(env.clone(), owner.clone(), token.clone());
+        env.storage()
+            .persistent()
+            .set(&DataKey::Claimable(owner.clone(), token.clone()), &(claimable + amount));
+    }
+
+    fn get_total(env: &Env, key: &DataKey) -> i128 {
+        env.storage().persistent().get(key).unwrap_or(0)
+    }
+
+    pub(crate) fn adjust_total(env: &Env, key: DataKey, delta: i128) {
+        let total = Self::get_total(env, &key);
+        env.storage().persistent().set(&key, &(total + delta));
+    }
+
+    fn release_open_job(env: &Env, agent_id: &String) {
+        let open_jobs = Self::get_open_jobs(env.clone(), agent_id.clone());
+        env.storage()
+            .persistent()
+            .set(&DataKey::OpenJobs(agent_id.clone()), &open_jobs.saturating_sub(1));
+    }
+
     fn add_to_hirer_jobs(env: &Env, hirer: &Address, job_id: u64) {
         let mut jobs: Vec<u64> = env
             .storage()
@@ -283,6 +1152,45 @@ impl AgentEscrowContract {
             .persistent()
             .set(&DataKey::JobsByOwner(owner.clone()), &jobs);
     }
+
+    fn add_to_agent_jobs(env: &Env, agent_id: &String, job_id: u64) {
+        let mut jobs: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::JobsByAgent(agent_id.clone()))
+            .unwrap_or(Vec::new(env));
+        
+        jobs.push_back(job_id);
+        env.storage()
+            .persistent()
+            .set(&DataKey::JobsByAgent(agent_id.clone()), &jobs);
+    }
+
+    fn add_to_status_jobs(env: &Env, agent_id: &String, status: JobStatus, job_id: u64) {
+        let key = DataKey::JobsByAgentStatus(agent_id.clone(), status);
+        let mut jobs: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(Vec::new(env));
+        
+        jobs.push_back(job_id);
+        env.storage().persistent().set(&key, &jobs);
+    }
+
+    fn remove_from_status_jobs(env: &Env, agent_id: &String, status: JobStatus, job_id: u64) {
+        let key = DataKey::JobsByAgentStatus(agent_id.clone(), status);
+        let mut jobs: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(Vec::new(env));
+        
+        if let Some(index) = jobs.first_index_of(job_id) {
+            jobs.remove(index);
+        }
+        env.storage().persistent().set(&key, &jobs);
+    }
 }
 
 #[cfg(test)]
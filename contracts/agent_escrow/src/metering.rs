@@ -0,0 +1,88 @@
+use soroban_sdk::{contractimpl, Address, BytesN, Env, String, Symbol};
+
+use crate::{
+    AgentEscrowContract, AgentEscrowContractArgs, AgentEscrowContractClient, Funding, JobKind,
+    JobStatus, Metering,
+};
+
+#[contractimpl]
+impl AgentEscrowContract {
+    /// Create a job paid by reported usage at the agent's registry unit price
+    ///
+    /// The budget is escrowed up front; whatever the reported usage does not
+    /// consume is refunded to the hirer when the job is finalized.
+    ///
+    /// # Arguments
+    /// * `hirer` - Address of the person hiring the agent
+    /// * `agent_id` - Unique identifier of the agent being hired
+    /// * `budget` - Maximum amount the job may cost
+    /// * `token` - Token contract address for payment
+    /// * `client_ref` - Optional idempotency key; repeating it returns the existing job ID
+    pub fn create_metered_job(
+        env: Env,
+        hirer: Address,
+        agent_id: String,
+        budget: i128,
+        token: Address,
+        client_ref: Option<BytesN<32>>,
+    ) -> u64 {
+        Self::require_not_paused(&env);
+
+        hirer.require_auth();
+
+        let agent = Self::registry_client(&env).get_agent(&agent_id);
+        assert!(agent.unit_price > 0, "Agent does not offer metered jobs");
+
+        let metering = Metering {
+            unit_price: agent.unit_price,
+            units: None,
+        };
+        Self::open_job(
+            &env,
+            hirer,
+            agent.owner,
+            agent_id,
+            budget,
+            token,
+            client_ref,
+            Funding::Hirer,
+            JobKind::Metered(metering),
+        )
+    }
+
+    /// Report the units a metered job consumed
+    ///
+    /// # Arguments
+    /// * `caller` - Agent owner, or an org admin or maintainer for organization agents
+    /// * `job_id` - ID of the metered job
+    /// * `units` - Units consumed, billed at the job's unit price
+    pub fn report_usage(env: Env, caller: Address, job_id: u64, units: u64) {
+        Self::require_not_paused(&env);
+
+        caller.require_auth();
+
+        let mut job = Self::load_job(&env, job_id);
+
+        // Only whoever acts for the agent reports usage
+        assert!(
+            Self::is_agent_operator(&env, &job.agent_id, &caller),
+            "Caller is not the agent owner"
+        );
+
+        assert!(job.status == JobStatus::Pending, "Job is not pending");
+        let JobKind::Metered(mut metering) = job.kind.clone() else {
+            panic!("Job is not metered");
+        };
+        assert!(metering.units.is_none(), "Usage already reported");
+
+        metering.units = Some(units);
+        job.kind = JobKind::Metered(metering);
+        Self::save_job(&env, &job);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "usage_reported"), job_id),
+            (units, Self::payout_amount(&job)),
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use soroban_sdk::{contractimpl, contracttype, Address, BytesN, Env, String, Symbol, TryFromVal, Val, Vec};
+
+use crate::{
+    AgentEscrowContract, AgentEscrowContractArgs, AgentEscrowContractClient, DataKey, Job, JobKind,
+    JobStatus,
+};
+
+/// Version of the contract code, bumped on every release
+const CODE_VERSION: u32 = 2;
+
+/// Version of the storage layout written by this code
+const SCHEMA_VERSION: u32 = 2;
+
+/// Job layout before storage versioning (schema 1)
+///
+/// Version 1 jobs did not record their payment token and paid out immediately
+/// on completion.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JobV1 {
+    pub id: u64,
+    pub hirer: Address,
+    pub agent_owner: Address,
+    pub agent_id: String,
+    pub amount: i128,
+    pub status: JobStatus,
+    pub created_at: u64,
+    pub completed_at: Option<u64>,
+    pub results_hash: Option<BytesN<32>>,
+}
+
+/// Versioned job record as stored under `DataKey::Job`
+///
+/// Older versions are upgraded to the newest variant when read.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum JobRecord {
+    V1(JobV1),
+    V2(Job),
+}
+
+/// Storage keys for migration state
+///
+/// Kept apart from `DataKey`, which is at the 50 variant limit for contract types.
+#[contracttype]
+pub enum MigrationKey {
+    LegacyToken,
+}
+
+/// Code and storage schema versions
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Version {
+    pub code: u32,
+    pub schema: u32,
+}
+
+#[contractimpl]
+impl AgentEscrowContract {
+    /// Get the code version and the storage schema version it writes
+    pub fn version(_env: Env) -> Version {
+        Version {
+            code: CODE_VERSION,
+            schema: SCHEMA_VERSION,
+        }
+    }
+
+    /// Set the token assumed for jobs written before storage versioning
+    ///
+    /// Version 1 jobs did not record the token they were paid in. Once this is
+    /// set, they are moved onto the current layout in this token when next
+    /// read. It can only be set once, right after the upgrade.
+    ///
+    /// # Arguments
+    /// * `token` - Token contract address most version 1 jobs were funded in
+    pub fn set_legacy_token(env: Env, token: Address) {
+        Self::get_admin(env.clone()).require_auth();
+
+        assert!(
+            !env.storage().persistent().has(&MigrationKey::LegacyToken),
+            "Legacy token already set"
+        );
+        env.storage().persistent().set(&MigrationKey::LegacyToken, &token);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "legacy_token_set"),),
+            token,
+        );
+    }
+
+    /// Get the token assumed for jobs written before storage versioning
+    pub fn get_legacy_token(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&MigrationKey::LegacyToken)
+    }
+
+    /// Move a job written before storage versioning onto the current layout
+    ///
+    /// For version 1 jobs funded in a token other than the legacy token; the
+    /// admin migrates them ahead of their first read. A job that still holds
+    /// funds is only accepted if the escrow's balance of that token covers it
+    /// on top of every tracked liability.
+    ///
+    /// # Arguments
+    /// * `job_id` - ID of the version 1 job
+    /// * `token` - Token contract address the job was funded in
+    pub fn migrate_legacy_job(env: Env, job_id: u64, token: Address) {
+        Self::get_admin(env.clone()).require_auth();
+
+        let JobRecord::V1(old) = Self::load_job_record(&env, job_id) else {
+            panic!("Job is already migrated");
+        };
+        Self::migrate_job_v1(&env, old, token);
+    }
+}
+
+impl AgentEscrowContract {
+    /// Read a job, upgrading records written by older schema versions
+    ///
+    /// Version 1 jobs are moved onto the current layout in the legacy token.
+    pub(crate) fn load_job(env: &Env, job_id: u64) -> Job {
+        match Self::load_job_record(env, job_id) {
+            JobRecord::V2(job) => job,
+            JobRecord::V1(old) => {
+                let token = Self::get_legacy_token(env.clone()).expect("Legacy token not set");
+                Self::migrate_job_v1(env, old, token)
+            }
+        }
+    }
+
+    fn load_job_record(env: &Env, job_id: u64) -> JobRecord {
+        let raw: Val = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Job(job_id))
+            .expect("Job not found");
+
+        // Records written before versioning are bare structs rather than enums
+        if Vec::<Val>::try_from_val(env, &raw).is_ok() {
+            JobRecord::try_from_val(env, &raw).expect("Invalid job record")
+        } else {
+            JobRecord::V1(JobV1::try_from_val(env, &raw).expect("Invalid job record"))
+        }
+    }
+
+    pub(crate) fn save_job(env: &Env, job: &Job) {
+        env.storage()
+            .persistent()
+            .set(&DataKey::Job(job.id), &JobRecord::V2(job.clone()));
+    }
+
+    /// Convert a version 1 job, backfill what it was missing and write it back
+    fn migrate_job_v1(env: &Env, old: JobV1, token: Address) -> Job {
+        // Version 1 paid the agent owner on completion, so a completed job (or
+        // one disputed after completion) no longer holds any funds
+        let status = match old.status {
+            JobStatus::Completed => JobStatus::Released,
+            JobStatus::Disputed if old.completed_at.is_some() => JobStatus::Released,
+            status => status,
+        };
+        let release_at = match status {
+            JobStatus::Released => old.completed_at,
+            _ => None,
+        };
+
+        // Backfill the indexes and totals introduced after version 1
+        Self::add_to_agent_jobs(env, &old.agent_id, old.id);
+        Self::add_to_status_jobs(env, &old.agent_id, status, old.id);
+        if status == JobStatus::Pending || status == JobStatus::Disputed {
+            let solvency = Self::get_solvency(env.clone(), token.clone());
+            assert!(
+                solvency.balance - solvency.liabilities >= old.amount,
+                "Token balance does not cover the job"
+            );
+            Self::adjust_total(env, DataKey::TotalEscrowed(token.clone()), old.amount);
+            let open_jobs = Self::get_open_jobs(env.clone(), old.agent_id.clone());
+            env.storage()
+                .persistent()
+                .set(&DataKey::OpenJobs(old.agent_id.clone()), &(open_jobs + 1));
+        }
+
+        let job = Job {
+            id: old.id,
+            hirer: old.hirer,
+            agent_owner: old.agent_owner,
+            agent_id: old.agent_id,
+            amount: old.amount,
+            token: token.clone(),
+            status,
+            created_at: old.created_at,
+            completed_at: old.completed_at,
+            release_at,
+            results_hash: old.results_hash,
+            kind: JobKind::Fixed,
+        };
+        Self::save_job(env, &job);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(env, "job_migrated"), job.id),
+            token,
+        );
+
+        job
+    }
+}
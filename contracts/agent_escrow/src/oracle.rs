@@ -0,0 +1,85 @@
+use soroban_sdk::{contractclient, contractimpl, contracttype, Address, Env, String, Symbol};
+
+use crate::{AgentEscrowContract, AgentEscrowContractArgs, AgentEscrowContractClient, DataKey};
+
+/// Asset identifier used by SEP-40 price feeds
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Asset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+/// Price reported by a SEP-40 price feed
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceData {
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+/// Subset of the SEP-40 price feed interface used by the escrow
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracleInterface {
+    fn base(env: Env) -> Asset;
+    fn decimals(env: Env) -> u32;
+    fn lastprice(env: Env, asset: Asset) -> Option<PriceData>;
+}
+
+/// Price oracle used to convert agent prices quoted in other currencies
+///
+/// The oracle's base asset is the quote currency it converts from, and
+/// prices older than `max_age` seconds are rejected.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceOracle {
+    pub address: Address,
+    pub max_age: u64,
+}
+
+#[contractimpl]
+impl AgentEscrowContract {
+    /// Get the price oracle settings (`None` if not configured)
+    pub fn get_price_oracle(env: Env) -> Option<PriceOracle> {
+        env.storage().persistent().get(&DataKey::PriceOracle)
+    }
+}
+
+impl AgentEscrowContract {
+    pub(crate) fn validate_price_oracle(oracle: &PriceOracle) {
+        assert!(oracle.max_age > 0, "Max price age must be positive");
+    }
+
+    /// Amount to escrow for a job, converting the agent's price if it is quoted
+    /// in another currency
+    ///
+    /// `amount` is what the hirer offered; for converted prices it is the most
+    /// they are willing to pay.
+    pub(crate) fn job_amount(env: &Env, agent_id: &String, token: &Address, amount: i128) -> i128 {
+        let agent = Self::registry_client(env).get_agent(agent_id);
+        let Some(currency) = agent.price_currency else {
+            return amount;
+        };
+
+        let config = Self::get_price_oracle(env.clone()).expect("Price oracle not configured");
+        let oracle = PriceOracleClient::new(env, &config.address);
+        assert!(
+            oracle.base() == Asset::Other(currency),
+            "Unsupported price currency"
+        );
+
+        let quote = oracle
+            .lastprice(&Asset::Stellar(token.clone()))
+            .expect("No price for token");
+        assert!(
+            env.ledger().timestamp().saturating_sub(quote.timestamp) <= config.max_age,
+            "Oracle price is stale"
+        );
+        assert!(quote.price > 0, "Invalid oracle price");
+
+        // Token amount = quoted price / token price, both in the oracle's decimals
+        let converted = agent.price * 10i128.pow(oracle.decimals()) / quote.price;
+        assert!(converted <= amount, "Price exceeds slippage limit");
+        converted
+    }
+}
@@ -0,0 +1,177 @@
+use soroban_sdk::{contractimpl, contracttype, token, Address, Bytes, BytesN, Env, String, Symbol, Vec};
+
+use crate::{
+    AgentEscrowContract, AgentEscrowContractArgs, AgentEscrowContractClient, DataKey, Funding, JobKind,
+    JobStatus, Stage,
+};
+
+/// Agent and payment for one pipeline stage
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StageSpec {
+    pub agent_id: String,
+    pub amount: i128,
+}
+
+/// Chain of jobs run one after another, funded together
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pipeline {
+    pub id: u64,
+    pub hirer: Address,
+    pub token: Address,
+    pub jobs: Vec<u64>,
+    pub created_at: u64,
+}
+
+#[contractimpl]
+impl AgentEscrowContract {
+    /// Fund a chain of agents in one transaction
+    ///
+    /// Every stage becomes a job paid to the agent's owner in the registry.
+    /// Only the first starts pending; each later stage unlocks when the one
+    /// before it completes, and its results hash is chained onto the
+    /// previous stage's.
+    ///
+    /// # Arguments
+    /// * `hirer` - Address of the person hiring the agents
+    /// * `token` - Token contract address for payment
+    /// * `stages` - Agents and amounts, in the order they run
+    pub fn create_pipeline(env: Env, hirer: Address, token: Address, stages: Vec<StageSpec>) -> u64 {
+        Self::require_not_paused(&env);
+
+        hirer.require_auth();
+
+        assert!(!stages.is_empty(), "Pipeline has no stages");
+
+        let pipeline_id: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::PipelineCounter)
+            .unwrap_or(0)
+            + 1;
+
+        let registry = Self::registry_client(&env);
+        let mut jobs = Vec::new(&env);
+        for (index, spec) in stages.iter().enumerate() {
+            assert!(spec.amount > 0, "Stage amount must be positive");
+            let agent = registry.get_agent(&spec.agent_id);
+            let stage = Stage {
+                pipeline_id,
+                index: index as u32,
+            };
+            let job_id = Self::open_job(
+                &env,
+                hirer.clone(),
+                agent.owner,
+                spec.agent_id,
+                spec.amount,
+                token.clone(),
+                None,
+                Funding::Hirer,
+                JobKind::Staged(stage),
+            );
+
+            // Later stages wait for the one before them
+            if index > 0 {
+                let mut job = Self::load_job(&env, job_id);
+                Self::set_status(&env, &mut job, JobStatus::Locked);
+                Self::save_job(&env, &job);
+            }
+            jobs.push_back(job_id);
+        }
+
+        let pipeline = Pipeline {
+            id: pipeline_id,
+            hirer: hirer.clone(),
+            token,
+            jobs: jobs.clone(),
+            created_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::Pipeline(pipeline_id), &pipeline);
+        env.storage()
+            .persistent()
+            .set(&DataKey::PipelineCounter, &pipeline_id);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "pipeline_created"), pipeline_id),
+            (hirer, jobs),
+        );
+
+        pipeline_id
+    }
+
+    /// Cancel every stage that has not started and refund it
+    ///
+    /// A stage already in progress is settled like any other job.
+    ///
+    /// # Arguments
+    /// * `pipeline_id` - ID of the pipeline
+    pub fn cancel_pipeline(env: Env, pipeline_id: u64) {
+        let pipeline = Self::get_pipeline(env.clone(), pipeline_id);
+
+        // Only the hirer can cancel
+        pipeline.hirer.require_auth();
+
+        let mut refunded: i128 = 0;
+        for job_id in pipeline.jobs.iter() {
+            let mut job = Self::load_job(&env, job_id);
+            if job.status != JobStatus::Locked {
+                continue;
+            }
+            Self::set_status(&env, &mut job, JobStatus::Cancelled);
+            Self::save_job(&env, &job);
+            Self::release_open_job(&env, &job.agent_id);
+            refunded += job.amount;
+        }
+        assert!(refunded > 0, "No stages left to cancel");
+
+        // Refund hirer
+        let client = token::Client::new(&env, &pipeline.token);
+        client.transfer(&env.current_contract_address(), &pipeline.hirer, &refunded);
+        Self::adjust_total(&env, DataKey::TotalEscrowed(pipeline.token.clone()), -refunded);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "pipeline_cancelled"), pipeline_id),
+            (pipeline.hirer, refunded),
+        );
+    }
+
+    /// Get pipeline details, with its stage job IDs in order
+    pub fn get_pipeline(env: Env, pipeline_id: u64) -> Pipeline {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Pipeline(pipeline_id))
+            .expect("Pipeline not found")
+    }
+}
+
+impl AgentEscrowContract {
+    /// Hash a stage's results together with the previous stage's chained hash
+    pub(crate) fn chain_stage_results(env: &Env, stage: &Stage, results_hash: BytesN<32>) -> BytesN<32> {
+        if stage.index == 0 {
+            return results_hash;
+        }
+        let pipeline = Self::get_pipeline(env.clone(), stage.pipeline_id);
+        let previous = Self::load_job(env, pipeline.jobs.get(stage.index - 1).unwrap());
+        let mut preimage = Bytes::from(previous.results_hash.expect("Previous stage has no results"));
+        preimage.append(&Bytes::from(results_hash));
+        env.crypto().sha256(&preimage).into()
+    }
+
+    pub(crate) fn unlock_next_stage(env: &Env, stage: &Stage) {
+        let pipeline = Self::get_pipeline(env.clone(), stage.pipeline_id);
+        let Some(job_id) = pipeline.jobs.get(stage.index + 1) else {
+            return;
+        };
+        let mut job = Self::load_job(env, job_id);
+        if job.status == JobStatus::Locked {
+            Self::set_status(env, &mut job, JobStatus::Pending);
+            Self::save_job(env, &job);
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use soroban_sdk::{contractimpl, contracttype, token, Address, Env, String, Symbol, Vec};
+
+use crate::{
+    AgentEscrowContract, AgentEscrowContractArgs, AgentEscrowContractClient, DataKey, Funding, Job,
+    JobKind, JobStatus, Pool,
+};
+
+/// Amount one contributor put into a crowdfunded job
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Contribution {
+    pub contributor: Address,
+    pub amount: i128,
+}
+
+#[contractimpl]
+impl AgentEscrowContract {
+    /// Open a crowdfunded job that several contributors pay for together
+    ///
+    /// The job stays in `Funding` until contributions reach the target, then
+    /// becomes pending like any other job. If the target is not reached by
+    /// the deadline every contributor can be refunded with `refund_pool`.
+    /// Payment goes to the agent's owner in the registry.
+    ///
+    /// # Arguments
+    /// * `hirer` - Organizer who completes or disputes the job for the contributors
+    /// * `agent_id` - Unique identifier of the agent being hired
+    /// * `target` - Amount that must be raised before the job starts
+    /// * `token` - Token contract address for payment
+    /// * `deadline` - Last time contributions are accepted
+    pub fn create_pooled_job(
+        env: Env,
+        hirer: Address,
+        agent_id: String,
+        target: i128,
+        token: Address,
+        deadline: u64,
+    ) -> u64 {
+        Self::require_not_paused(&env);
+
+        hirer.require_auth();
+
+        assert!(target > 0, "Target must be positive");
+        assert!(deadline > env.ledger().timestamp(), "Deadline must be in the future");
+        Self::require_supported_token(&env, &token, target);
+
+        // Nothing is escrowed until contributions arrive
+        let agent = Self::registry_client(&env).get_agent(&agent_id);
+        let pool = Pool { deadline, raised: 0 };
+        let job_id = Self::open_job(
+            &env,
+            hirer,
+            agent.owner,
+            agent_id,
+            target,
+            token,
+            None,
+            Funding::Escrowed,
+            JobKind::Pooled(pool),
+        );
+        let mut job = Self::load_job(&env, job_id);
+        Self::set_status(&env, &mut job, JobStatus::Funding);
+        Self::save_job(&env, &job);
+
+        job_id
+    }
+
+    /// Contribute towards a crowdfunded job
+    ///
+    /// The contribution that reaches the target starts the job.
+    ///
+    /// # Arguments
+    /// * `contributor` - Address paying in
+    /// * `job_id` - ID of the crowdfunded job
+    /// * `amount` - Amount contributed, at most what is still needed
+    pub fn contribute(env: Env, contributor: Address, job_id: u64, amount: i128) {
+        Self::require_not_paused(&env);
+
+        contributor.require_auth();
+
+        let mut job = Self::load_job(&env, job_id);
+        let mut pool = Self::load_pool(&job);
+        assert!(job.status == JobStatus::Funding, "Job is not funding");
+        assert!(env.ledger().timestamp() <= pool.deadline, "Funding deadline has passed");
+        assert!(amount > 0, "Contribution must be positive");
+        assert!(amount <= job.amount - pool.raised, "Contribution exceeds target");
+
+        let client = token::Client::new(&env, &job.token);
+        client.transfer(&contributor, &env.current_contract_address(), &amount);
+        Self::adjust_total(&env, DataKey::TotalEscrowed(job.token.clone()), amount);
+
+        // Repeat contributions add to the contributor's share
+        let mut contributions = Self::get_contributions(env.clone(), job_id);
+        match contributions.iter().position(|c| c.contributor == contributor) {
+            Some(index) => {
+                let mut contribution = contributions.get(index as u32).unwrap();
+                contribution.amount += amount;
+                contributions.set(index as u32, contribution);
+            }
+            None => contributions.push_back(Contribution {
+                contributor: contributor.clone(),
+                amount,
+            }),
+        }
+        env.storage()
+            .persistent()
+            .set(&DataKey::Contributions(job_id), &contributions);
+
+        pool.raised += amount;
+        let funded = pool.raised == job.amount;
+        job.kind = JobKind::Pooled(pool);
+        if funded {
+            Self::set_status(&env, &mut job, JobStatus::Pending);
+        }
+        Self::save_job(&env, &job);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "contribution_received"), job_id),
+            (contributor, amount),
+        );
+        if funded {
+            env.events().publish(
+                (Symbol::new(&env, "job_funded"), job_id),
+                job.amount,
+            );
+        }
+    }
+
+    /// Refund every contributor of a job that missed its funding target
+    ///
+    /// Anyone may call this once the deadline has passed. Each contribution
+    /// is credited back to its contributor's claimable balance.
+    ///
+    /// # Arguments
+    /// * `job_id` - ID of the crowdfunded job
+    pub fn refund_pool(env: Env, job_id: u64) {
+        let mut job = Self::load_job(&env, job_id);
+        let pool = Self::load_pool(&job);
+        assert!(job.status == JobStatus::Funding, "Job is not funding");
+        assert!(env.ledger().timestamp() > pool.deadline, "Funding deadline has not passed");
+
+        Self::set_status(&env, &mut job, JobStatus::Cancelled);
+        Self::save_job(&env, &job);
+        Self::release_open_job(&env, &job.agent_id);
+
+        // Everyone gets back exactly what they put in
+        Self::adjust_total(&env, DataKey::TotalEscrowed(job.token.clone()), -pool.raised);
+        for contribution in Self::get_contributions(env.clone(), job_id) {
+            Self::credit_claimable(&env, &contribution.contributor, &job.token, contribution.amount);
+        }
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "pool_refunded"), job_id),
+            pool.raised,
+        );
+    }
+
+    /// Get everyone who contributed to a crowdfunded job and how much
+    pub fn get_contributions(env: Env, job_id: u64) -> Vec<Contribution> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Contributions(job_id))
+            .unwrap_or(Vec::new(&env))
+    }
+}
+
+impl AgentEscrowContract {
+    /// Split a refund between the parties that funded a job
+    ///
+    /// Crowdfunded jobs refund contributors pro-rata to what they put in, with
+    /// any rounding remainder going to the first contributor. Every other job
+    /// refunds its hirer.
+    pub(crate) fn refund_shares(env: &Env, job: &Job, amount: i128) -> Vec<(Address, i128)> {
+        let mut shares = Vec::new(env);
+        if !matches!(job.kind, JobKind::Pooled(_)) {
+            shares.push_back((job.hirer.clone(), amount));
+            return shares;
+        }
+
+        let contributions = Self::get_contributions(env.clone(), job.id);
+        let mut remainder = amount;
+        for contribution in contributions.iter() {
+            let share = amount * contribution.amount / job.amount;
+            remainder -= share;
+            shares.push_back((contribution.contributor, share));
+        }
+        if let Some((first, share)) = shares.first() {
+            shares.set(0, (first, share + remainder));
+        }
+        shares
+    }
+
+    fn load_pool(job: &Job) -> Pool {
+        match &job.kind {
+            JobKind::Pooled(pool) => pool.clone(),
+            _ => panic!("Job is not crowdfunded"),
+        }
+    }
+}
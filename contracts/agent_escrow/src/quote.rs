@@ -0,0 +1,88 @@
+use soroban_sdk::{contractimpl, contracttype, xdr::ToXdr, Address, BytesN, Env, String};
+
+use crate::{
+    AgentEscrowContract, AgentEscrowContractArgs, AgentEscrowContractClient, DataKey, Funding, JobKind,
+};
+
+/// Price quote signed off-chain by an agent's quote key
+///
+/// The key signs the XDR encoding of `(escrow_address, quote)`, so a quote
+/// can only be used with the escrow it was issued for.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Quote {
+    pub agent_id: String,
+    pub hirer: Address,
+    pub amount: i128,
+    pub token: Address,
+    pub expiry: u64,
+    pub nonce: u64,
+}
+
+#[contractimpl]
+impl AgentEscrowContract {
+    /// Create a job at a price quoted by the agent owner
+    ///
+    /// Payment goes to the agent's owner in the registry.
+    ///
+    /// # Arguments
+    /// * `hirer` - Address of the person hiring the agent; must match the quote
+    /// * `quote` - Quote terms
+    /// * `signature` - Ed25519 signature from the agent's registered quote key
+    /// * `client_ref` - Optional idempotency key; repeating it returns the existing job ID
+    pub fn create_job_with_quote(
+        env: Env,
+        hirer: Address,
+        quote: Quote,
+        signature: BytesN<64>,
+        client_ref: Option<BytesN<32>>,
+    ) -> u64 {
+        Self::require_not_paused(&env);
+
+        hirer.require_auth();
+
+        // A retry after the quote was spent should still find its job
+        if let Some(job_id) = Self::existing_job(&env, &hirer, &client_ref) {
+            return job_id;
+        }
+
+        assert!(quote.hirer == hirer, "Quote is for another hirer");
+        assert!(env.ledger().timestamp() <= quote.expiry, "Quote expired");
+        assert!(
+            !Self::is_quote_used(env.clone(), quote.agent_id.clone(), quote.nonce),
+            "Quote already used"
+        );
+
+        // Verify the agent owner signed these terms for this escrow
+        let registry = Self::registry_client(&env);
+        let quote_key = registry
+            .get_quote_key(&quote.agent_id)
+            .expect("Agent has no quote key");
+        let message = (env.current_contract_address(), quote.clone()).to_xdr(&env);
+        env.crypto().ed25519_verify(&quote_key, &message, &signature);
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::QuoteNonce(quote.agent_id.clone(), quote.nonce), &true);
+
+        let agent = registry.get_agent(&quote.agent_id);
+        Self::open_job(
+            &env,
+            hirer,
+            agent.owner,
+            quote.agent_id,
+            quote.amount,
+            quote.token,
+            client_ref,
+            Funding::Hirer,
+            JobKind::Fixed,
+        )
+    }
+
+    /// Whether a quote nonce has already been used for an agent
+    pub fn is_quote_used(env: Env, agent_id: String, nonce: u64) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::QuoteNonce(agent_id, nonce))
+    }
+}
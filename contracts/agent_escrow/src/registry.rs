@@ -0,0 +1,30 @@
+use soroban_sdk::{contractclient, contracttype, Address, BytesN, Env, String, Symbol, Vec};
+
+/// Agent metadata as stored by the agent registry contract
+///
+/// Must stay field-for-field identical to `agent_registry::AgentInfo`,
+/// otherwise decoding the registry's return value fails.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AgentInfo {
+    pub agent_id: String,
+    pub owner: Address,
+    pub price: i128,
+    pub metadata_uri: String,
+    pub is_active: bool,
+    pub registered_at: u64,
+    pub max_open_jobs: u32,
+    pub org_id: Option<String>,
+    pub unit_price: i128,
+    pub price_currency: Option<Symbol>,
+}
+
+/// Subset of the agent registry interface used by the escrow
+#[contractclient(name = "RegistryClient")]
+pub trait AgentRegistryInterface {
+    fn get_agent(env: Env, agent_id: String) -> AgentInfo;
+    fn get_quote_key(env: Env, agent_id: String) -> Option<BytesN<32>>;
+    fn get_agent_tags(env: Env, agent_id: String) -> Vec<Symbol>;
+    fn get_verifier(env: Env, agent_id: String) -> Option<Address>;
+    fn is_agent_operator(env: Env, agent_id: String, caller: Address) -> bool;
+}
@@ -0,0 +1,170 @@
+use soroban_sdk::{contractimpl, Address, BytesN, Env, String, Symbol};
+
+use crate::{
+    AgentEscrowContract, AgentEscrowContractArgs, AgentEscrowContractClient, DataKey, Funding, Job,
+    JobKind, JobStatus, Stream,
+};
+
+#[contractimpl]
+impl AgentEscrowContract {
+    /// Create a job that pays the agent owner continuously over a time window
+    ///
+    /// The full amount is escrowed up front and accrues linearly from `start`
+    /// to `end`. The owner can withdraw what has accrued at any time, and
+    /// either party can stop the stream early. Payment goes to the agent's
+    /// owner in the registry.
+    ///
+    /// # Arguments
+    /// * `hirer` - Address of the person hiring the agent
+    /// * `agent_id` - Unique identifier of the agent being hired
+    /// * `amount` - Total amount streamed over the window
+    /// * `token` - Token contract address for payment
+    /// * `start` - Timestamp the stream starts accruing
+    /// * `end` - Timestamp the full amount has accrued
+    /// * `client_ref` - Optional idempotency key; repeating it returns the existing job ID
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_stream_job(
+        env: Env,
+        hirer: Address,
+        agent_id: String,
+        amount: i128,
+        token: Address,
+        start: u64,
+        end: u64,
+        client_ref: Option<BytesN<32>>,
+    ) -> u64 {
+        Self::require_not_paused(&env);
+
+        hirer.require_auth();
+
+        assert!(start >= env.ledger().timestamp(), "Stream cannot start in the past");
+        assert!(end > start, "Stream must end after it starts");
+
+        let agent = Self::registry_client(&env).get_agent(&agent_id);
+        let stream = Stream {
+            start,
+            end,
+            withdrawn: 0,
+        };
+        Self::open_job(
+            &env,
+            hirer,
+            agent.owner,
+            agent_id,
+            amount,
+            token,
+            client_ref,
+            Funding::Hirer,
+            JobKind::Streamed(stream),
+        )
+    }
+
+    /// Credit everything a stream has accrued so far to the agent owner's claimable balance
+    ///
+    /// Returns the amount credited before platform fees. The job is released
+    /// once the full amount has been credited.
+    ///
+    /// # Arguments
+    /// * `job_id` - ID of the streamed job
+    pub fn withdraw_stream(env: Env, job_id: u64) -> i128 {
+        let mut job = Self::load_job(&env, job_id);
+
+        // Only the agent owner withdraws
+        job.agent_owner.require_auth();
+
+        assert!(job.status == JobStatus::Pending, "Job is not pending");
+        let mut stream = Self::stream_of(&job);
+
+        let available = Self::accrued(&env, &job, &stream) - stream.withdrawn;
+        assert!(available > 0, "Nothing accrued");
+
+        stream.withdrawn += available;
+        Self::pay_stream(&env, &job, available);
+
+        let fully_paid = stream.withdrawn == job.amount;
+        job.kind = JobKind::Streamed(stream);
+        if fully_paid {
+            Self::close_stream(&env, &mut job);
+        }
+        Self::save_job(&env, &job);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "stream_withdrawn"), job_id),
+            (job.agent_owner, available),
+        );
+
+        available
+    }
+
+    /// Stop a stream early, crediting what has accrued to the owner and the rest to the hirer
+    ///
+    /// # Arguments
+    /// * `caller` - Hirer or agent owner of the job
+    /// * `job_id` - ID of the streamed job
+    pub fn stop_stream(env: Env, caller: Address, job_id: u64) {
+        caller.require_auth();
+
+        let mut job = Self::load_job(&env, job_id);
+
+        assert!(
+            caller == job.hirer || Self::is_agent_operator(&env, &job.agent_id, &caller),
+            "Only hirer or agent owner can stop"
+        );
+        assert!(job.status == JobStatus::Pending, "Job is not pending");
+        let mut stream = Self::stream_of(&job);
+
+        // Settle the accrued balance with the owner
+        let accrued = Self::accrued(&env, &job, &stream);
+        let paid = accrued - stream.withdrawn;
+        if paid > 0 {
+            Self::pay_stream(&env, &job, paid);
+        }
+        stream.withdrawn = accrued;
+
+        // Refund what has not accrued yet
+        let refunded = job.amount - accrued;
+        if refunded > 0 {
+            Self::adjust_total(&env, DataKey::TotalEscrowed(job.token.clone()), -refunded);
+            Self::credit_claimable(&env, &job.hirer, &job.token, refunded);
+        }
+
+        job.kind = JobKind::Streamed(stream);
+        Self::close_stream(&env, &mut job);
+        Self::save_job(&env, &job);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "stream_stopped"), job_id),
+            (caller, paid, refunded),
+        );
+    }
+}
+
+impl AgentEscrowContract {
+    fn stream_of(job: &Job) -> Stream {
+        match &job.kind {
+            JobKind::Streamed(stream) => stream.clone(),
+            _ => panic!("Job is not streamed"),
+        }
+    }
+
+    /// Amount of the stream that has accrued as of now
+    fn accrued(env: &Env, job: &Job, stream: &Stream) -> i128 {
+        let now = env.ledger().timestamp().clamp(stream.start, stream.end);
+        let elapsed = (now - stream.start) as i128;
+        let duration = (stream.end - stream.start) as i128;
+        job.amount * elapsed / duration
+    }
+
+    fn pay_stream(env: &Env, job: &Job, amount: i128) {
+        Self::adjust_total(env, DataKey::TotalEscrowed(job.token.clone()), -amount);
+        Self::credit_payout(env, &job.agent_owner, &job.token, amount);
+    }
+
+    fn close_stream(env: &Env, job: &mut Job) {
+        job.completed_at = Some(env.ledger().timestamp());
+        Self::set_status(env, job, JobStatus::Released);
+        Self::release_open_job(env, &job.agent_id);
+    }
+}
@@ -0,0 +1,193 @@
+use soroban_sdk::{contractimpl, Address, Env, String, Symbol, Vec};
+
+use crate::{
+    AgentEscrowContract, AgentEscrowContractArgs, AgentEscrowContractClient, DataKey, Funding, Job,
+    JobKind, JobStatus,
+};
+
+#[contractimpl]
+impl AgentEscrowContract {
+    /// Subcontract part of a pending job to another agent
+    ///
+    /// The child job is funded from the parent's escrow, with the caller as
+    /// its hirer. The parent keeps whatever is left over as its
+    /// payout, and refunds from the child go back to the parent. The parent
+    /// cannot settle until all its children have.
+    ///
+    /// Completing a child also needs the parent's hirer, and children are
+    /// frozen while the parent is in dispute.
+    ///
+    /// # Arguments
+    /// * `caller` - Owner of the parent's agent, or an org admin or maintainer for organization agents
+    /// * `parent_id` - ID of the pending job being subcontracted
+    /// * `agent_id` - Agent hired for the child job; paid to its registry owner
+    /// * `amount` - Part of the parent's escrow that funds the child
+    pub fn create_child_job(
+        env: Env,
+        caller: Address,
+        parent_id: u64,
+        agent_id: String,
+        amount: i128,
+    ) -> u64 {
+        Self::require_not_paused(&env);
+
+        caller.require_auth();
+
+        let mut parent = Self::load_job(&env, parent_id);
+
+        // Only whoever acts for the parent's agent can subcontract
+        assert!(
+            Self::is_agent_operator(&env, &parent.agent_id, &caller),
+            "Caller is not the agent owner"
+        );
+
+        assert!(parent.status == JobStatus::Pending, "Job is not pending");
+        assert!(parent.kind == JobKind::Fixed, "Only fixed-price jobs can subcontract");
+        assert!(
+            Self::get_parent_job(env.clone(), parent_id).is_none(),
+            "Child jobs cannot subcontract"
+        );
+        assert!(amount > 0, "Amount must be positive");
+        assert!(amount <= parent.amount, "Amount exceeds parent escrow");
+
+        let agent = Self::registry_client(&env).get_agent(&agent_id);
+        let child_id = Self::open_job(
+            &env,
+            caller,
+            agent.owner,
+            agent_id.clone(),
+            amount,
+            parent.token.clone(),
+            None,
+            Funding::Escrowed,
+            JobKind::Fixed,
+        );
+
+        // The funds move from the parent to the child within the escrow
+        parent.amount -= amount;
+        Self::save_job(&env, &parent);
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::ParentJob(child_id), &parent_id);
+        let mut children = Self::get_child_jobs(env.clone(), parent_id);
+        children.push_back(child_id);
+        env.storage()
+            .persistent()
+            .set(&DataKey::ChildJobs(parent_id), &children);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "child_job_created"), parent_id),
+            (child_id, agent_id, amount),
+        );
+
+        child_id
+    }
+
+    /// Get the job a child job was subcontracted from (`None` for top-level jobs)
+    pub fn get_parent_job(env: Env, job_id: u64) -> Option<u64> {
+        env.storage().persistent().get(&DataKey::ParentJob(job_id))
+    }
+
+    /// Get the jobs subcontracted from a job
+    pub fn get_child_jobs(env: Env, job_id: u64) -> Vec<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ChildJobs(job_id))
+            .unwrap_or(Vec::new(&env))
+    }
+}
+
+impl AgentEscrowContract {
+    pub(crate) fn require_children_settled(env: &Env, job_id: u64) {
+        for child_id in Self::get_child_jobs(env.clone(), job_id) {
+            let status = Self::load_job(env, child_id).status;
+            assert!(
+                matches!(
+                    status,
+                    JobStatus::Released | JobStatus::Resolved | JobStatus::Cancelled
+                ),
+                "Child jobs are not settled"
+            );
+        }
+    }
+
+    /// Require every child to be out of pending and dispute
+    ///
+    /// Completed children may still be holding their payout; a refund from
+    /// them later goes to the parent's hirer.
+    pub(crate) fn require_no_open_children(env: &Env, job_id: u64) {
+        for child_id in Self::get_child_jobs(env.clone(), job_id) {
+            let status = Self::load_job(env, child_id).status;
+            assert!(
+                status != JobStatus::Pending && status != JobStatus::Disputed,
+                "Child jobs are not settled"
+            );
+        }
+    }
+
+    /// Require the parent's hirer to approve completing a child job
+    ///
+    /// The child's hirer acts for the parent's agent, and could otherwise pay
+    /// an agent of their own out of the parent's escrow.
+    pub(crate) fn require_parent_approval(env: &Env, job: &Job) {
+        if let Some(parent) = Self::load_parent(env, job) {
+            parent.hirer.require_auth();
+            assert!(parent.status != JobStatus::Disputed, "Parent job is in dispute");
+        }
+    }
+
+    /// Require a child job's parent not to be in dispute
+    pub(crate) fn require_parent_not_disputed(env: &Env, job: &Job) {
+        if let Some(parent) = Self::load_parent(env, job) {
+            assert!(parent.status != JobStatus::Disputed, "Parent job is in dispute");
+        }
+    }
+
+    /// Cancel a disputed job's pending children, returning their funds to it
+    ///
+    /// The funds stay escrowed, so the dispute settles the whole amount.
+    pub(crate) fn reclaim_pending_children(env: &Env, parent: &mut Job) {
+        for child_id in Self::get_child_jobs(env.clone(), parent.id) {
+            let mut child = Self::load_job(env, child_id);
+            if child.status != JobStatus::Pending {
+                continue;
+            }
+            Self::set_status(env, &mut child, JobStatus::Cancelled);
+            Self::save_job(env, &child);
+            Self::release_open_job(env, &child.agent_id);
+            parent.amount += child.amount;
+
+            // Emit event
+            env.events().publish(
+                (Symbol::new(env, "job_cancelled"), child_id),
+                (child.hirer, child.amount),
+            );
+        }
+    }
+
+    /// Put a child job's refund back into its parent's escrow
+    ///
+    /// A parent that has already settled cannot take funds back, so the refund
+    /// is credited to its hirer instead. Returns `false` if the job has no
+    /// parent, leaving the refund to the caller.
+    pub(crate) fn return_to_parent(env: &Env, job: &Job, amount: i128) -> bool {
+        let Some(mut parent) = Self::load_parent(env, job) else {
+            return false;
+        };
+        if parent.status == JobStatus::Pending || parent.status == JobStatus::Disputed {
+            parent.amount += amount;
+            Self::save_job(env, &parent);
+            Self::adjust_total(env, DataKey::TotalEscrowed(job.token.clone()), amount);
+        } else {
+            Self::credit_claimable(env, &parent.hirer, &job.token, amount);
+        }
+        true
+    }
+
+    fn load_parent(env: &Env, job: &Job) -> Option<Job> {
+        let parent_id = Self::get_parent_job(env.clone(), job.id)?;
+        Some(Self::load_job(env, parent_id))
+    }
+}
@@ -0,0 +1,294 @@
+use soroban_sdk::{contractimpl, contracttype, token, Address, Env, String, Symbol, Vec};
+
+use crate::registry::AgentInfo;
+use crate::{
+    AgentEscrowContract, AgentEscrowContractArgs, AgentEscrowContractClient, DataKey, Funding, JobKind,
+};
+
+/// How an open task picks the agent that does it
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ClaimMode {
+    /// The first eligible agent to claim gets the job
+    FirstCome,
+    /// Agents claim and the hirer picks one of them
+    HirerSelects,
+    /// Agents bid a price up to the reward and the hirer picks a winning bid
+    Auction,
+}
+
+/// Task lifecycle states
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TaskStatus {
+    Open,
+    Assigned,
+    Cancelled,
+}
+
+/// Reward posted by a hirer for any agent advertising the required tag
+///
+/// For auctions the reward is the hirer's maximum budget and the deadline
+/// closes bidding.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Task {
+    pub id: u64,
+    pub hirer: Address,
+    pub reward: i128,
+    pub token: Address,
+    pub tag: Symbol,
+    pub deadline: u64,
+    pub mode: ClaimMode,
+    pub status: TaskStatus,
+    pub job_id: Option<u64>,
+    pub created_at: u64,
+}
+
+#[contractimpl]
+impl AgentEscrowContract {
+    /// Post an open task that any active agent with the tag can claim
+    ///
+    /// The reward is escrowed now and becomes a regular job for the agent that
+    /// gets the task.
+    ///
+    /// # Arguments
+    /// * `hirer` - Address posting the task
+    /// * `reward` - Payment for the agent that does the task (the budget for auctions)
+    /// * `token` - Token contract address for payment
+    /// * `tag` - Capability tag the agent must have in the registry
+    /// * `deadline` - Last time agents can claim or bid on the task
+    /// * `mode` - Whether the first claim wins, the hirer selects, or agents bid
+    pub fn post_task(
+        env: Env,
+        hirer: Address,
+        reward: i128,
+        token: Address,
+        tag: Symbol,
+        deadline: u64,
+        mode: ClaimMode,
+    ) -> u64 {
+        Self::require_not_paused(&env);
+
+        hirer.require_auth();
+
+        assert!(reward > 0, "Reward must be positive");
+        assert!(deadline > env.ledger().timestamp(), "Deadline must be in the future");
+        Self::require_supported_token(&env, &token, reward);
+
+        // Escrow the reward until the task is assigned or cancelled
+        let client = token::Client::new(&env, &token);
+        client.transfer(&hirer, &env.current_contract_address(), &reward);
+        Self::adjust_total(&env, DataKey::TotalEscrowed(token.clone()), reward);
+
+        let task_id: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::TaskCounter)
+            .unwrap_or(0)
+            + 1;
+        let task = Task {
+            id: task_id,
+            hirer: hirer.clone(),
+            reward,
+            token,
+            tag: tag.clone(),
+            deadline,
+            mode,
+            status: TaskStatus::Open,
+            job_id: None,
+            created_at: env.ledger().timestamp(),
+        };
+        env.storage().persistent().set(&DataKey::Task(task_id), &task);
+        env.storage().persistent().set(&DataKey::TaskCounter, &task_id);
+
+        let mut open_tasks = Self::get_open_tasks(env.clone());
+        open_tasks.push_back(task_id);
+        env.storage().persistent().set(&DataKey::OpenTasks, &open_tasks);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "task_posted"), task_id),
+            (hirer, reward, tag, deadline),
+        );
+
+        task_id
+    }
+
+    /// Claim an open task for an agent
+    ///
+    /// First-come tasks are assigned straight away and return the new job ID;
+    /// otherwise the claim waits for the hirer and `None` is returned.
+    ///
+    /// # Arguments
+    /// * `caller` - Owner of the agent in the registry
+    /// * `task_id` - ID of the task
+    /// * `agent_id` - Agent that will do the task
+    pub fn claim_task(env: Env, caller: Address, task_id: u64, agent_id: String) -> Option<u64> {
+        Self::require_not_paused(&env);
+
+        caller.require_auth();
+
+        let mut task = Self::get_task(env.clone(), task_id);
+        assert!(task.status == TaskStatus::Open, "Task is not open");
+        assert!(task.mode != ClaimMode::Auction, "Auction tasks take bids");
+        assert!(env.ledger().timestamp() <= task.deadline, "Task deadline has passed");
+
+        let agent = Self::registry_client(&env).get_agent(&agent_id);
+        assert!(
+            Self::is_agent_operator(&env, &agent_id, &caller),
+            "Caller is not the agent owner"
+        );
+        Self::require_eligible_agent(&env, &task, &agent);
+
+        if task.mode == ClaimMode::FirstCome {
+            let reward = task.reward;
+            return Some(Self::assign_task(&env, &mut task, agent, reward));
+        }
+
+        let mut claims = Self::get_task_claims(env.clone(), task_id);
+        assert!(!claims.contains(&agent_id), "Agent already claimed the task");
+        claims.push_back(agent_id.clone());
+        env.storage().persistent().set(&DataKey::TaskClaims(task_id), &claims);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "task_claimed"), task_id),
+            agent_id,
+        );
+
+        None
+    }
+
+    /// Pick one of the agents that claimed a hirer-selects task
+    ///
+    /// # Arguments
+    /// * `task_id` - ID of the task
+    /// * `agent_id` - Claiming agent to hire
+    pub fn select_claim(env: Env, task_id: u64, agent_id: String) -> u64 {
+        Self::require_not_paused(&env);
+
+        let mut task = Self::get_task(env.clone(), task_id);
+
+        // Only the hirer can select
+        task.hirer.require_auth();
+
+        assert!(task.status == TaskStatus::Open, "Task is not open");
+        assert!(task.mode == ClaimMode::HirerSelects, "Task is not hirer-selects");
+        assert!(
+            Self::get_task_claims(env.clone(), task_id).contains(&agent_id),
+            "Agent has not claimed the task"
+        );
+
+        // The listing may have changed since the claim
+        let agent = Self::registry_client(&env).get_agent(&agent_id);
+        Self::require_eligible_agent(&env, &task, &agent);
+
+        let reward = task.reward;
+        Self::assign_task(&env, &mut task, agent, reward)
+    }
+
+    /// Withdraw an open task and refund its reward
+    ///
+    /// # Arguments
+    /// * `task_id` - ID of the task
+    pub fn cancel_task(env: Env, task_id: u64) {
+        let mut task = Self::get_task(env.clone(), task_id);
+
+        // Only the hirer can cancel
+        task.hirer.require_auth();
+
+        assert!(task.status == TaskStatus::Open, "Task is not open");
+        task.status = TaskStatus::Cancelled;
+        env.storage().persistent().set(&DataKey::Task(task_id), &task);
+        Self::remove_open_task(&env, task_id);
+
+        // Refund hirer
+        let client = token::Client::new(&env, &task.token);
+        client.transfer(&env.current_contract_address(), &task.hirer, &task.reward);
+        Self::adjust_total(&env, DataKey::TotalEscrowed(task.token.clone()), -task.reward);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "task_cancelled"), task_id),
+            (task.hirer, task.reward),
+        );
+    }
+
+    /// Get task details
+    pub fn get_task(env: Env, task_id: u64) -> Task {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Task(task_id))
+            .expect("Task not found")
+    }
+
+    /// Get the IDs of all tasks still open to claims
+    pub fn get_open_tasks(env: Env) -> Vec<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::OpenTasks)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Get the agents waiting on the hirer's selection for a task (claims or bids)
+    pub fn get_task_claims(env: Env, task_id: u64) -> Vec<String> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::TaskClaims(task_id))
+            .unwrap_or(Vec::new(&env))
+    }
+}
+
+impl AgentEscrowContract {
+    pub(crate) fn require_eligible_agent(env: &Env, task: &Task, agent: &AgentInfo) {
+        assert!(agent.is_active, "Agent is not active");
+        let tags = Self::registry_client(env).get_agent_tags(&agent.agent_id);
+        assert!(tags.contains(&task.tag), "Agent lacks the required tag");
+    }
+
+    /// Turn a task into a job for the agent, funded by the escrowed reward
+    ///
+    /// Any part of the reward above `amount` is refunded to the hirer.
+    pub(crate) fn assign_task(env: &Env, task: &mut Task, agent: AgentInfo, amount: i128) -> u64 {
+        let job_id = Self::open_job(
+            env,
+            task.hirer.clone(),
+            agent.owner,
+            agent.agent_id.clone(),
+            amount,
+            task.token.clone(),
+            None,
+            Funding::Escrowed,
+            JobKind::Fixed,
+        );
+
+        let refund = task.reward - amount;
+        if refund > 0 {
+            let client = token::Client::new(env, &task.token);
+            client.transfer(&env.current_contract_address(), &task.hirer, &refund);
+            Self::adjust_total(env, DataKey::TotalEscrowed(task.token.clone()), -refund);
+        }
+
+        task.status = TaskStatus::Assigned;
+        task.job_id = Some(job_id);
+        env.storage().persistent().set(&DataKey::Task(task.id), &*task);
+        Self::remove_open_task(env, task.id);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(env, "task_assigned"), task.id),
+            (agent.agent_id, job_id),
+        );
+
+        job_id
+    }
+
+    fn remove_open_task(env: &Env, task_id: u64) {
+        let mut open_tasks = Self::get_open_tasks(env.clone());
+        if let Some(index) = open_tasks.first_index_of(task_id) {
+            open_tasks.remove(index);
+        }
+        env.storage().persistent().set(&DataKey::OpenTasks, &open_tasks);
+    }
+}
@@ -1,7 +1,27 @@
 #![cfg(test)]
 
+extern crate std;
+
 use super::*;
-use soroban_sdk::{testutils::Address as _, token, Env};
+use allowance::Allowance;
+use auction::Bid;
+use jury::{JuryConfig, JuryOutcome, Verdict};
+use oracle::{Asset, PriceData, PriceOracle};
+use pipeline::StageSpec;
+use pool::Contribution;
+use quote::Quote;
+use tasks::{ClaimMode, TaskStatus};
+use timelock::AdminOp;
+use tokens::SupportedToken;
+use agent_registry::{org::Role, AgentRegistryContract, AgentRegistryContractClient};
+use ed25519_dalek::{Signer, SigningKey};
+use soroban_sdk::{
+    contract, contractimpl,
+    testutils::{Address as _, Events, Ledger},
+    token, vec,
+    xdr::ToXdr,
+    Bytes, Env, IntoVal,
+};
 
 fn create_token_contract<'a>(env: &Env, admin: &Address) -> (token::Client<'a>, token::StellarAssetClient<'a>) {
     let token_address = env.register_stellar_asset_contract_v2(admin.clone());
@@ -11,6 +31,95 @@ fn create_token_contract<'a>(env: &Env, admin: &Address) -> (token::Client<'a>,
     )
 }
 
+fn create_registry_contract<'a>(
+    env: &Env,
+    agent_owner: &Address,
+    agent_ids: &[&str],
+) -> AgentRegistryContractClient<'a> {
+    let registry = AgentRegistryContractClient::new(env, &env.register(AgentRegistryContract, ()));
+    for agent_id in agent_ids {
+        registry.register_agent(
+            &String::from_str(env, agent_id),
+            agent_owner,
+            &100,
+            &String::from_str(env, "ipfs://QmTest123"),
+        );
+    }
+    registry
+}
+
+/// SEP-40 style price feed quoting every asset in USD with 14 decimals
+#[contract]
+pub struct MockOracle;
+
+#[contractimpl]
+impl MockOracle {
+    pub fn set_price(env: Env, price: i128, timestamp: u64) {
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, "price"), &PriceData { price, timestamp });
+    }
+
+    pub fn base(env: Env) -> Asset {
+        Asset::Other(Symbol::new(&env, "USD"))
+    }
+
+    pub fn decimals(_env: Env) -> u32 {
+        14
+    }
+
+    pub fn lastprice(env: Env, _asset: Asset) -> Option<PriceData> {
+        env.storage().instance().get(&Symbol::new(&env, "price"))
+    }
+}
+
+/// Verifier that accepts results whose proof is the results hash itself
+#[contract]
+pub struct MockVerifier;
+
+#[contractimpl]
+impl MockVerifier {
+    pub fn verify(
+        _env: Env,
+        _job_id: u64,
+        inputs_hash: BytesN<32>,
+        results_hash: BytesN<32>,
+        proof: Bytes,
+    ) -> bool {
+        let mut expected = Bytes::from(inputs_hash);
+        expected.append(&Bytes::from(results_hash));
+        proof == expected
+    }
+}
+
+fn run_admin_op(env: &Env, client: &AgentEscrowContractClient, op: AdminOp) {
+    let eta = env.ledger().timestamp() + client.get_min_delay();
+    let op_id = client.schedule_op(&op, &eta);
+    env.ledger().with_mut(|li| li.timestamp = eta);
+    client.execute_op(&op_id);
+}
+
+fn assert_solvent(client: &AgentEscrowContractClient, token: &Address) {
+    let solvency = client.get_solvency(token);
+    assert!(solvency.is_solvent);
+    assert_eq!(solvency.balance, solvency.liabilities);
+}
+
+fn vote_commitment(env: &Env, verdict: Verdict, salt: &BytesN<32>) -> BytesN<32> {
+    let mut preimage = Bytes::new(env);
+    preimage.push_back(match verdict {
+        Verdict::Hirer => 0,
+        Verdict::Agent => 1,
+    });
+    preimage.append(&Bytes::from(salt));
+    env.crypto().sha256(&preimage).into()
+}
+
+fn sign_quote(env: &Env, escrow: &Address, signing_key: &SigningKey, quote: &Quote) -> BytesN<64> {
+    let message: std::vec::Vec<u8> = (escrow.clone(), quote.clone()).to_xdr(env).iter().collect();
+    BytesN::from_array(env, &signing_key.sign(&message).to_bytes())
+}
+
 #[test]
 fn test_create_job() {
     let env = Env::default();
@@ -29,16 +138,23 @@ fn test_create_job() {
     token_admin_client.mint(&hirer, &1000);
 
     // Initialize contract
-    client.initialize();
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
 
     // Create job
     let job_id = client.create_job(
         &hirer,
-        &agent_owner,
         &String::from_str(&env, "test-agent"),
         &100,
         &token_client.address,
+        &None,
+        &None,
+        &None,
     );
+    assert_solvent(&client, &token_client.address);
 
     assert_eq!(job_id, 1);
 
@@ -48,6 +164,7 @@ fn test_create_job() {
     assert_eq!(job.hirer, hirer);
     assert_eq!(job.agent_owner, agent_owner);
     assert_eq!(job.amount, 100);
+    assert_eq!(job.token, token_client.address);
     assert_eq!(job.status, JobStatus::Pending);
 
     // Verify escrow holds the funds
@@ -73,20 +190,28 @@ fn test_complete_job() {
     token_admin_client.mint(&hirer, &1000);
 
     // Initialize and create job
-    client.initialize();
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
     let job_id = client.create_job(
         &hirer,
-        &agent_owner,
         &String::from_str(&env, "test-agent"),
         &100,
         &token_client.address,
+        &None,
+        &None,
+        &None,
     );
+    assert_solvent(&client, &token_client.address);
 
     // Create results hash
     let results_hash = BytesN::from_array(&env, &[1u8; 32]);
 
     // Complete job
     client.complete_job(&job_id, &results_hash, &token_client.address);
+    assert_solvent(&client, &token_client.address);
 
     // Verify job status
     let job = client.get_job(&job_id);
@@ -94,7 +219,22 @@ fn test_complete_job() {
     assert!(job.completed_at.is_some());
     assert_eq!(job.results_hash, Some(results_hash));
 
-    // Verify payment was released
+    // Payment is held during the challenge window
+    assert_eq!(job.release_at, Some(86_400));
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 0);
+
+    // Once the window passes anyone can release it to the agent owner
+    env.ledger().with_mut(|li| li.timestamp = 86_400);
+    client.finalize(&job_id);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_job(&job_id).status, JobStatus::Released);
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 100);
+    assert_eq!(token_client.balance(&contract_id), 100);
+
+    // Owner pulls the payment out
+    client.withdraw(&agent_owner, &token_client.address, &100);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 0);
     assert_eq!(token_client.balance(&contract_id), 0);
     assert_eq!(token_client.balance(&agent_owner), 100);
 }
@@ -117,17 +257,25 @@ fn test_cancel_job() {
     token_admin_client.mint(&hirer, &1000);
 
     // Initialize and create job
-    client.initialize();
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
     let job_id = client.create_job(
         &hirer,
-        &agent_owner,
         &String::from_str(&env, "test-agent"),
         &100,
         &token_client.address,
+        &None,
+        &None,
+        &None,
     );
+    assert_solvent(&client, &token_client.address);
 
     // Cancel job
     client.cancel_job(&job_id, &token_client.address);
+    assert_solvent(&client, &token_client.address);
 
     // Verify job status
     let job = client.get_job(&job_id);
@@ -156,17 +304,25 @@ fn test_dispute_job() {
     token_admin_client.mint(&hirer, &1000);
 
     // Initialize and create job
-    client.initialize();
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
     let job_id = client.create_job(
         &hirer,
-        &agent_owner,
         &String::from_str(&env, "test-agent"),
         &100,
         &token_client.address,
+        &None,
+        &None,
+        &None,
     );
+    assert_solvent(&client, &token_client.address);
 
     // Initiate dispute (from hirer)
     client.dispute_job(&hirer, &job_id);
+    assert_solvent(&client, &token_client.address);
 
     // Verify job status
     let job = client.get_job(&job_id);
@@ -194,24 +350,34 @@ fn test_get_jobs_by_hirer() {
     token_admin_client.mint(&hirer, &3000);
 
     // Initialize contract
-    client.initialize();
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["agent-1", "agent-2"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
 
     // Create multiple jobs
     let job_id1 = client.create_job(
         &hirer,
-        &agent_owner,
         &String::from_str(&env, "agent-1"),
         &100,
         &token_client.address,
+        &None,
+        &None,
+        &None,
     );
+    assert_solvent(&client, &token_client.address);
     
     let job_id2 = client.create_job(
         &hirer,
-        &agent_owner,
         &String::from_str(&env, "agent-2"),
         &200,
         &token_client.address,
+        &None,
+        &None,
+        &None,
     );
+    assert_solvent(&client, &token_client.address);
 
     // Get jobs by hirer
     let jobs = client.get_jobs_by_hirer(&hirer);
@@ -219,3 +385,2151 @@ fn test_get_jobs_by_hirer() {
     assert_eq!(jobs.get(0).unwrap(), job_id1);
     assert_eq!(jobs.get(1).unwrap(), job_id2);
 }
+
+#[test]
+fn test_open_jobs_released_on_settlement() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    let agent_id = String::from_str(&env, "test-agent");
+    registry.set_max_open_jobs(&agent_owner, &agent_id, &1);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    // Fill the agent's only slot
+    let job_id = client.create_job(&hirer, &agent_id, &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_open_jobs(&agent_id), 1);
+
+    // Cancelling frees the slot for a new job
+    client.cancel_job(&job_id, &token_client.address);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_open_jobs(&agent_id), 0);
+
+    let job_id = client.create_job(&hirer, &agent_id, &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    client.complete_job(&job_id, &BytesN::from_array(&env, &[1u8; 32]), &token_client.address);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_open_jobs(&agent_id), 0);
+}
+
+#[test]
+#[should_panic(expected = "Agent is at capacity")]
+fn test_create_job_rejected_at_capacity() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    let agent_id = String::from_str(&env, "test-agent");
+    registry.set_max_open_jobs(&agent_owner, &agent_id, &2);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    client.create_job(&hirer, &agent_id, &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    client.create_job(&hirer, &agent_id, &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+
+    // Third concurrent job exceeds the limit
+    client.create_job(&hirer, &agent_id, &100, &token_client.address, &None, &None, &None);
+}
+
+#[test]
+fn test_get_jobs_by_agent() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["agent-1", "agent-2"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    let agent_1 = String::from_str(&env, "agent-1");
+    let agent_2 = String::from_str(&env, "agent-2");
+    let job_id1 = client.create_job(&hirer, &agent_1, &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    let job_id2 = client.create_job(&hirer, &agent_1, &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    let job_id3 = client.create_job(&hirer, &agent_1, &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    client.create_job(&hirer, &agent_2, &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+
+    // Empty jobs would hold a capacity slot and clutter the indexes
+    assert!(client.try_create_job(&hirer, &agent_2, &0, &token_client.address, &None, &None, &None).is_err());
+    assert_eq!(client.get_open_jobs(&agent_2), 1);
+
+    // Move jobs through different statuses
+    client.complete_job(&job_id1, &BytesN::from_array(&env, &[1u8; 32]), &token_client.address);
+    assert_solvent(&client, &token_client.address);
+    client.cancel_job(&job_id2, &token_client.address);
+    assert_solvent(&client, &token_client.address);
+
+    // Unfiltered index keeps every job for the agent
+    let jobs = client.get_jobs_by_agent(&agent_1, &None, &0, &10);
+    assert_eq!(jobs.len(), 3);
+
+    // Pagination
+    let jobs = client.get_jobs_by_agent(&agent_1, &None, &1, &1);
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs.get(0).unwrap(), job_id2);
+    assert_eq!(client.get_jobs_by_agent(&agent_1, &None, &5, &10).len(), 0);
+
+    // Status-filtered indexes follow status changes
+    let pending = client.get_jobs_by_agent(&agent_1, &Some(JobStatus::Pending), &0, &10);
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending.get(0).unwrap(), job_id3);
+
+    let completed = client.get_jobs_by_agent(&agent_1, &Some(JobStatus::Completed), &0, &10);
+    assert_eq!(completed.len(), 1);
+    assert_eq!(completed.get(0).unwrap(), job_id1);
+
+    client.dispute_job(&hirer, &job_id1);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_jobs_by_agent(&agent_1, &Some(JobStatus::Completed), &0, &10).len(), 0);
+    assert_eq!(client.get_jobs_by_agent(&agent_1, &Some(JobStatus::Disputed), &0, &10).len(), 1);
+    assert_eq!(client.get_jobs_by_agent(&agent_2, &Some(JobStatus::Pending), &0, &10).len(), 1);
+}
+
+#[test]
+fn test_create_job_with_client_ref_is_idempotent() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let other_hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+    token_admin_client.mint(&other_hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    let agent_id = String::from_str(&env, "test-agent");
+    let client_ref = Some(BytesN::from_array(&env, &[7u8; 32]));
+    assert_eq!(client.get_job_by_ref(&hirer, &client_ref.clone().unwrap()), None);
+
+    // Retrying with the same reference returns the original job without funding it again
+    let job_id = client.create_job(&hirer, &agent_id, &100, &token_client.address, &client_ref, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    let retry_id = client.create_job(&hirer, &agent_id, &100, &token_client.address, &client_ref, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(retry_id, job_id);
+    assert_eq!(token_client.balance(&hirer), 900);
+    assert_eq!(client.get_jobs_by_hirer(&hirer).len(), 1);
+    assert_eq!(client.get_job_by_ref(&hirer, &client_ref.clone().unwrap()), Some(job_id));
+
+    // References are scoped per hirer
+    let other_id = client.create_job(&other_hirer, &agent_id, &100, &token_client.address, &client_ref, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    assert_ne!(other_id, job_id);
+    assert_eq!(token_client.balance(&contract_id), 200);
+}
+
+#[test]
+fn test_withdraw_in_batches() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["agent-1", "agent-2"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    // Earnings from several jobs accumulate
+    let results_hash = BytesN::from_array(&env, &[1u8; 32]);
+    let job_id1 = client.create_job(&hirer, &String::from_str(&env, "agent-1"), &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    let job_id2 = client.create_job(&hirer, &String::from_str(&env, "agent-2"), &250, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    client.complete_job(&job_id1, &results_hash, &token_client.address);
+    assert_solvent(&client, &token_client.address);
+    client.complete_job(&job_id2, &results_hash, &token_client.address);
+    assert_solvent(&client, &token_client.address);
+    env.ledger().with_mut(|li| li.timestamp = 86_400);
+    client.finalize(&job_id1);
+    assert_solvent(&client, &token_client.address);
+    client.finalize(&job_id2);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 350);
+
+    // Partial withdrawal leaves the rest claimable
+    client.withdraw(&agent_owner, &token_client.address, &200);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 150);
+    assert_eq!(token_client.balance(&agent_owner), 200);
+    assert_eq!(token_client.balance(&contract_id), 150);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient claimable balance")]
+fn test_cannot_withdraw_more_than_claimable() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    // Funds of a pending job are not claimable
+    client.create_job(&hirer, &String::from_str(&env, "test-agent"), &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    client.withdraw(&agent_owner, &token_client.address, &100);
+}
+
+#[test]
+#[should_panic(expected = "Token mismatch")]
+fn test_complete_job_rejects_other_token() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    let (other_token_client, _) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    let job_id = client.create_job(&hirer, &String::from_str(&env, "test-agent"), &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    client.complete_job(&job_id, &BytesN::from_array(&env, &[1u8; 32]), &other_token_client.address);
+}
+
+#[test]
+fn test_dispute_during_challenge_window_claws_back_funds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+    run_admin_op(&env, &client, AdminOp::SetChallengePeriod(3_600));
+    assert_solvent(&client, &token_client.address);
+
+    let job_id = client.create_job(&hirer, &String::from_str(&env, "test-agent"), &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    client.complete_job(&job_id, &BytesN::from_array(&env, &[1u8; 32]), &token_client.address);
+    assert_solvent(&client, &token_client.address);
+
+    // Hirer disputes before the window closes, freezing the payout
+    let completed_at = env.ledger().timestamp();
+    env.ledger().with_mut(|li| li.timestamp = completed_at + 3_599);
+    client.dispute_job(&hirer, &job_id);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_job(&job_id).status, JobStatus::Disputed);
+    assert!(client.try_finalize(&job_id).is_err());
+
+    // Arbiter splits the held funds
+    client.resolve_dispute(&job_id, &70);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_job(&job_id).status, JobStatus::Resolved);
+    assert_eq!(client.get_claimable(&hirer, &token_client.address), 70);
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 30);
+    assert_eq!(token_client.balance(&contract_id), 100);
+}
+
+#[test]
+fn test_resolve_dispute_on_pending_job_frees_capacity() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let arbiter = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+    run_admin_op(&env, &client, AdminOp::SetArbiter(arbiter.clone()));
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_arbiter(), arbiter);
+
+    let agent_id = String::from_str(&env, "test-agent");
+    let job_id = client.create_job(&hirer, &agent_id, &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    client.dispute_job(&agent_owner, &job_id);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_open_jobs(&agent_id), 1);
+
+    client.resolve_dispute(&job_id, &100);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_open_jobs(&agent_id), 0);
+    assert_eq!(client.get_claimable(&hirer, &token_client.address), 100);
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 0);
+}
+
+#[test]
+#[should_panic(expected = "Challenge window still open")]
+fn test_cannot_finalize_during_challenge_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    let job_id = client.create_job(&hirer, &String::from_str(&env, "test-agent"), &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    client.complete_job(&job_id, &BytesN::from_array(&env, &[1u8; 32]), &token_client.address);
+    assert_solvent(&client, &token_client.address);
+
+    env.ledger().with_mut(|li| li.timestamp = 86_399);
+    client.finalize(&job_id);
+}
+
+#[test]
+#[should_panic(expected = "Challenge window has closed")]
+fn test_cannot_dispute_after_challenge_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    let job_id = client.create_job(&hirer, &String::from_str(&env, "test-agent"), &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    client.complete_job(&job_id, &BytesN::from_array(&env, &[1u8; 32]), &token_client.address);
+    assert_solvent(&client, &token_client.address);
+
+    env.ledger().with_mut(|li| li.timestamp = 86_400);
+    client.dispute_job(&hirer, &job_id);
+}
+
+#[test]
+fn test_submit_evidence() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    let job_id = client.create_job(&hirer, &String::from_str(&env, "test-agent"), &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    client.dispute_job(&hirer, &job_id);
+    assert_solvent(&client, &token_client.address);
+
+    let dispute = client.get_dispute(&job_id);
+    assert_eq!(dispute.initiator, hirer);
+    assert_eq!(dispute.opened_at, 1_000);
+    assert_eq!(dispute.response_deadline, 1_000 + 259_200);
+
+    // Both parties append evidence
+    let hirer_hash = BytesN::from_array(&env, &[1u8; 32]);
+    let owner_hash = BytesN::from_array(&env, &[2u8; 32]);
+    client.submit_evidence(&hirer, &job_id, &hirer_hash, &String::from_str(&env, "ipfs://QmHirer"));
+    assert_solvent(&client, &token_client.address);
+    client.submit_evidence(&agent_owner, &job_id, &owner_hash, &String::from_str(&env, "ipfs://QmOwner"));
+    assert_solvent(&client, &token_client.address);
+
+    let evidence = client.get_evidence(&job_id);
+    assert_eq!(evidence.len(), 2);
+    assert_eq!(evidence.get(0).unwrap().submitter, hirer);
+    assert_eq!(evidence.get(0).unwrap().evidence_hash, hirer_hash);
+    assert_eq!(evidence.get(1).unwrap().submitter, agent_owner);
+    assert_eq!(evidence.get(1).unwrap().uri, String::from_str(&env, "ipfs://QmOwner"));
+
+    // Once the other side responded there is no default outcome
+    env.ledger().with_mut(|li| li.timestamp = 1_000 + 259_200);
+    assert!(client.try_claim_default(&hirer, &job_id).is_err());
+}
+
+#[test]
+fn test_claim_default_when_other_party_is_silent() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+    run_admin_op(&env, &client, AdminOp::SetResponsePeriod(600));
+    assert_solvent(&client, &token_client.address);
+
+    let agent_id = String::from_str(&env, "test-agent");
+    let job_id = client.create_job(&hirer, &agent_id, &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    client.dispute_job(&hirer, &job_id);
+    assert_solvent(&client, &token_client.address);
+    let opened_at = env.ledger().timestamp();
+    client.submit_evidence(&hirer, &job_id, &BytesN::from_array(&env, &[1u8; 32]), &String::from_str(&env, "ipfs://QmHirer"));
+    assert_solvent(&client, &token_client.address);
+
+    // Too early to claim
+    env.ledger().with_mut(|li| li.timestamp = opened_at + 599);
+    assert!(client.try_claim_default(&hirer, &job_id).is_err());
+
+    // The silent side cannot claim the default outcome
+    env.ledger().with_mut(|li| li.timestamp = opened_at + 600);
+    assert!(client.try_claim_default(&agent_owner, &job_id).is_err());
+
+    client.claim_default(&hirer, &job_id);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_job(&job_id).status, JobStatus::Resolved);
+    assert_eq!(client.get_claimable(&hirer, &token_client.address), 100);
+    assert_eq!(client.get_open_jobs(&agent_id), 0);
+
+    // A responder wins against an initiator who never backed the dispute
+    let job_id = client.create_job(&hirer, &agent_id, &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    client.dispute_job(&agent_owner, &job_id);
+    assert_solvent(&client, &token_client.address);
+    let opened_at = env.ledger().timestamp();
+    env.ledger().with_mut(|li| li.timestamp = opened_at + 300);
+    client.submit_evidence(&hirer, &job_id, &BytesN::from_array(&env, &[2u8; 32]), &String::from_str(&env, "ipfs://QmHirer"));
+    assert_solvent(&client, &token_client.address);
+
+    env.ledger().with_mut(|li| li.timestamp = opened_at + 600);
+    assert!(client.try_claim_default(&agent_owner, &job_id).is_err());
+    client.claim_default(&hirer, &job_id);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_job(&job_id).status, JobStatus::Resolved);
+    assert_eq!(client.get_claimable(&hirer, &token_client.address), 200);
+
+    // Evidence sent after the deadline neither blocks nor wins the default
+    let job_id = client.create_job(&hirer, &agent_id, &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    client.dispute_job(&agent_owner, &job_id);
+    assert_solvent(&client, &token_client.address);
+    let opened_at = env.ledger().timestamp();
+    env.ledger().with_mut(|li| li.timestamp = opened_at + 600);
+    client.submit_evidence(&hirer, &job_id, &BytesN::from_array(&env, &[3u8; 32]), &String::from_str(&env, "ipfs://QmLate"));
+    assert_solvent(&client, &token_client.address);
+    assert!(client.try_claim_default(&hirer, &job_id).is_err());
+
+    client.claim_default(&agent_owner, &job_id);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_job(&job_id).status, JobStatus::Resolved);
+    assert_eq!(client.get_claimable(&hirer, &token_client.address), 200);
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 100);
+}
+
+#[test]
+fn test_org_agent_members_act_for_the_agent() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let org_admin = Address::generate(&env);
+    let maintainer = Address::generate(&env);
+    let finance = Address::generate(&env);
+    let payout = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &org_admin, &[]);
+    let org_id = String::from_str(&env, "acme");
+    let agent_id = String::from_str(&env, "acme-agent");
+    registry.create_org(&org_admin, &org_id);
+    registry.set_member_role(&org_admin, &org_id, &maintainer, &Role::Maintainer);
+    registry.set_member_role(&org_admin, &org_id, &finance, &Role::Finance);
+    registry.register_org_agent(&maintainer, &org_id, &agent_id, &payout, &100, &String::from_str(&env, "ipfs://QmTest123"));
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    run_admin_op(&env, &client, AdminOp::SetResponsePeriod(600));
+    assert_solvent(&client, &token_client.address);
+
+    let job_id = client.create_job(&hirer, &agent_id, &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_job(&job_id).agent_owner, payout);
+
+    // The payout address and finance members only receive payments
+    assert!(client.try_dispute_job(&payout, &job_id).is_err());
+    assert!(client.try_dispute_job(&finance, &job_id).is_err());
+    client.dispute_job(&maintainer, &job_id);
+    assert_solvent(&client, &token_client.address);
+    let opened_at = env.ledger().timestamp();
+    assert!(client
+        .try_submit_evidence(&payout, &job_id, &BytesN::from_array(&env, &[1u8; 32]), &String::from_str(&env, "ipfs://QmPayout"))
+        .is_err());
+
+    // Any admin or maintainer can claim for the agent side
+    env.ledger().with_mut(|li| li.timestamp = opened_at + 600);
+    assert!(client.try_claim_default(&payout, &job_id).is_err());
+    assert!(client.try_claim_default(&hirer, &job_id).is_err());
+    client.claim_default(&org_admin, &job_id);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_claimable(&payout, &token_client.address), 100);
+    assert_eq!(client.get_claimable(&org_admin, &token_client.address), 0);
+}
+
+#[test]
+fn test_jury_lifecycle() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    let (stake_client, stake_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+    assert_solvent(&client, &stake_client.address);
+
+    // Panel of 3, half the stake slashed for voting against the majority
+    run_admin_op(
+        &env,
+        &client,
+        AdminOp::ConfigureJury(JuryConfig {
+            stake_token: stake_client.address.clone(),
+            min_stake: 100,
+            panel_size: 3,
+            commit_period: 3_600,
+            reveal_period: 3_600,
+            slash_bps: 5_000,
+        }),
+    );
+    assert_solvent(&client, &token_client.address);
+
+    let jurors = [
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    ];
+    for juror in jurors.iter() {
+        stake_admin_client.mint(juror, &100);
+        client.join_jury(juror, &100);
+        assert_solvent(&client, &token_client.address);
+        assert_solvent(&client, &stake_client.address);
+    }
+    assert_eq!(client.get_jurors().len(), 4);
+    assert_eq!(stake_client.balance(&contract_id), 400);
+
+    // Case 1: full commit/reveal round with a 2-1 majority for the hirer
+    let agent_id = String::from_str(&env, "test-agent");
+    let job_id = client.create_job(&hirer, &agent_id, &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    assert_solvent(&client, &stake_client.address);
+    client.dispute_job(&hirer, &job_id);
+    assert_solvent(&client, &token_client.address);
+    assert_solvent(&client, &stake_client.address);
+    client.escalate_to_jury(&hirer, &job_id);
+    assert_solvent(&client, &token_client.address);
+    assert_solvent(&client, &stake_client.address);
+
+    let case = client.get_jury_case(&job_id);
+    assert_eq!(case.panel.len(), 3);
+    assert_eq!(case.outcome, JuryOutcome::Voting);
+    let drawn_at = env.ledger().timestamp();
+    assert_eq!(case.commit_deadline, drawn_at + 3_600);
+    assert_eq!(case.reveal_deadline, drawn_at + 7_200);
+
+    // The arbiter cannot step in and panelists cannot unstake mid-case
+    assert!(client.try_resolve_dispute(&job_id, &0).is_err());
+    assert!(client.try_leave_jury(&case.panel.get(0).unwrap(), &100).is_err());
+
+    let votes = [Verdict::Hirer, Verdict::Hirer, Verdict::Agent];
+    for (i, juror) in case.panel.iter().enumerate() {
+        let salt = BytesN::from_array(&env, &[i as u8; 32]);
+        client.commit_vote(&juror, &job_id, &vote_commitment(&env, votes[i], &salt));
+        assert_solvent(&client, &token_client.address);
+        assert_solvent(&client, &stake_client.address);
+    }
+
+    // Reveals only open after the commit phase
+    let first = case.panel.get(0).unwrap();
+    assert!(client.try_reveal_vote(&first, &job_id, &votes[0], &BytesN::from_array(&env, &[0u8; 32])).is_err());
+
+    env.ledger().with_mut(|li| li.timestamp = case.commit_deadline);
+    // A reveal that does not match the commitment is rejected
+    assert!(client.try_reveal_vote(&first, &job_id, &Verdict::Agent, &BytesN::from_array(&env, &[0u8; 32])).is_err());
+    for (i, juror) in case.panel.iter().enumerate() {
+        let salt = BytesN::from_array(&env, &[i as u8; 32]);
+        client.reveal_vote(&juror, &job_id, &votes[i], &salt);
+        assert_solvent(&client, &token_client.address);
+        assert_solvent(&client, &stake_client.address);
+    }
+    assert!(client.try_finalize_jury(&job_id).is_err());
+
+    env.ledger().with_mut(|li| li.timestamp = case.reveal_deadline);
+    client.finalize_jury(&job_id);
+    assert_solvent(&client, &token_client.address);
+    assert_solvent(&client, &stake_client.address);
+    assert_eq!(client.get_jury_case(&job_id).outcome, JuryOutcome::Hirer);
+    assert_eq!(client.get_job(&job_id).status, JobStatus::Resolved);
+    assert_eq!(client.get_claimable(&hirer, &token_client.address), 100);
+
+    // Minority juror loses half their stake to the majority
+    assert_eq!(client.get_juror_stake(&case.panel.get(0).unwrap()), 125);
+    assert_eq!(client.get_juror_stake(&case.panel.get(1).unwrap()), 125);
+    assert_eq!(client.get_juror_stake(&case.panel.get(2).unwrap()), 50);
+    assert_eq!(client.get_jurors().len(), 3);
+    assert_eq!(stake_client.balance(&contract_id), 400);
+
+    // Case 2: only one juror shows up before the timeout
+    for juror in jurors.iter() {
+        let stake = client.get_juror_stake(juror);
+        if stake < 100 {
+            stake_admin_client.mint(juror, &(100 - stake));
+            client.join_jury(juror, &(100 - stake));
+            assert_solvent(&client, &token_client.address);
+            assert_solvent(&client, &stake_client.address);
+        }
+    }
+    let job_id = client.create_job(&hirer, &agent_id, &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    assert_solvent(&client, &stake_client.address);
+    client.dispute_job(&agent_owner, &job_id);
+    assert_solvent(&client, &token_client.address);
+    assert_solvent(&client, &stake_client.address);
+    client.escalate_to_jury(&agent_owner, &job_id);
+    assert_solvent(&client, &token_client.address);
+    assert_solvent(&client, &stake_client.address);
+    assert!(client.try_escalate_to_jury(&hirer, &job_id).is_err());
+
+    let case = client.get_jury_case(&job_id);
+    let voter = case.panel.get(0).unwrap();
+    let salt = BytesN::from_array(&env, &[9u8; 32]);
+    client.commit_vote(&voter, &job_id, &vote_commitment(&env, Verdict::Agent, &salt));
+    assert_solvent(&client, &token_client.address);
+    assert_solvent(&client, &stake_client.address);
+    env.ledger().with_mut(|li| li.timestamp = case.commit_deadline);
+    client.reveal_vote(&voter, &job_id, &Verdict::Agent, &salt);
+    assert_solvent(&client, &token_client.address);
+    assert_solvent(&client, &stake_client.address);
+
+    env.ledger().with_mut(|li| li.timestamp = case.reveal_deadline);
+    let voter_stake = client.get_juror_stake(&voter);
+    let absent_stakes: i128 = case.panel.iter().skip(1).map(|juror| client.get_juror_stake(&juror)).sum();
+    client.finalize_jury(&job_id);
+    assert_solvent(&client, &token_client.address);
+    assert_solvent(&client, &stake_client.address);
+
+    // No majority of the panel: absent jurors are slashed, the dispute falls back to the arbiter
+    assert_eq!(client.get_jury_case(&job_id).outcome, JuryOutcome::NoMajority);
+    assert_eq!(client.get_job(&job_id).status, JobStatus::Disputed);
+    assert_eq!(client.get_juror_stake(&voter), voter_stake + absent_stakes / 2);
+    client.resolve_dispute(&job_id, &0);
+    assert_solvent(&client, &token_client.address);
+    assert_solvent(&client, &stake_client.address);
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 100);
+
+    // Stakes are withdrawable again once all cases are closed
+    let stake = client.get_juror_stake(&voter);
+    client.leave_jury(&voter, &stake);
+    assert_solvent(&client, &token_client.address);
+    assert_solvent(&client, &stake_client.address);
+    assert_eq!(stake_client.balance(&voter), stake);
+    assert_eq!(client.get_juror_stake(&voter), 0);
+}
+
+#[test]
+fn test_pause_blocks_new_jobs_but_not_refunds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    let agent_id = String::from_str(&env, "test-agent");
+    let results_hash = BytesN::from_array(&env, &[1u8; 32]);
+    let job_id1 = client.create_job(&hirer, &agent_id, &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    let job_id2 = client.create_job(&hirer, &agent_id, &100, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+
+    client.pause();
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (Symbol::new(&env, "contract_paused"), admin.clone()).into_val(&env),
+                ().into_val(&env),
+            ),
+        ]
+    );
+    assert!(client.is_paused());
+    assert_solvent(&client, &token_client.address);
+
+    // State-changing entry points are blocked
+    assert!(client.try_create_job(&hirer, &agent_id, &100, &token_client.address, &None, &None, &None).is_err());
+    assert!(client.try_complete_job(&job_id1, &results_hash, &token_client.address).is_err());
+    assert!(client.try_dispute_job(&hirer, &job_id1).is_err());
+
+    // Refunds still work
+    client.cancel_job(&job_id1, &token_client.address);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(token_client.balance(&hirer), 900);
+
+    client.unpause();
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                contract_id.clone(),
+                (Symbol::new(&env, "contract_unpaused"), admin.clone()).into_val(&env),
+                ().into_val(&env),
+            ),
+        ]
+    );
+    assert!(!client.is_paused());
+    assert_solvent(&client, &token_client.address);
+    client.complete_job(&job_id2, &results_hash, &token_client.address);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_job(&job_id2).status, JobStatus::Completed);
+}
+
+#[test]
+fn test_timelocked_arbiter_change() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let new_arbiter = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_min_delay(), 172_800);
+
+    // ETA must respect the minimum delay
+    let op = AdminOp::SetArbiter(new_arbiter.clone());
+    assert!(client.try_schedule_op(&op, &172_799).is_err());
+
+    // Anyone can inspect the queue while the change is pending
+    let op_id = client.schedule_op(&op, &172_800);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_pending_ops().len(), 1);
+    let scheduled = client.get_scheduled_op(&op_id);
+    assert_eq!(scheduled.op, op);
+    assert_eq!(scheduled.eta, 172_800);
+
+    assert!(client.try_execute_op(&op_id).is_err());
+    assert_eq!(client.get_arbiter(), admin);
+    env.ledger().with_mut(|li| li.timestamp = 172_800);
+    client.execute_op(&op_id);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_arbiter(), new_arbiter);
+    assert_eq!(client.get_pending_ops().len(), 0);
+    assert!(client.try_execute_op(&op_id).is_err());
+
+    // The new arbiter settles disputes
+    let job_id = client.create_job(&hirer, &String::from_str(&env, "test-agent"), &1000, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    client.dispute_job(&hirer, &job_id);
+    assert_solvent(&client, &token_client.address);
+    client.resolve_dispute(&job_id, &250);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_claimable(&hirer, &token_client.address), 250);
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 750);
+}
+
+#[test]
+fn test_timelocked_fee_change() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    // Fees above the cap never reach the queue
+    assert!(client.try_schedule_op(&AdminOp::SetFee(5_000), &172_800).is_err());
+
+    let op_id = client.schedule_op(&AdminOp::SetFee(250), &172_800);
+    assert_solvent(&client, &token_client.address);
+    assert!(client.try_execute_op(&op_id).is_err());
+    assert_eq!(client.get_fee_bps(), 0);
+    env.ledger().with_mut(|li| li.timestamp = 172_800);
+    client.execute_op(&op_id);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_fee_bps(), 250);
+
+    // The fee is taken from the agent owner's payout
+    let job_id = client.create_job(&hirer, &String::from_str(&env, "test-agent"), &1000, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    client.complete_job(&job_id, &BytesN::from_array(&env, &[1u8; 32]), &token_client.address);
+    assert_solvent(&client, &token_client.address);
+    env.ledger().with_mut(|li| li.timestamp += 86_400);
+    client.finalize(&job_id);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 975);
+    assert_eq!(client.get_fee_balance(&token_client.address), 25);
+
+    client.withdraw_fees(&treasury, &token_client.address, &25);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(token_client.balance(&treasury), 25);
+    assert_eq!(client.get_fee_balance(&token_client.address), 0);
+}
+
+#[test]
+fn test_cancel_scheduled_op() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let agent_owner = Address::generate(&env);
+    let new_arbiter = Address::generate(&env);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+
+    let op_id = client.schedule_op(&AdminOp::SetArbiter(new_arbiter), &200_000);
+    let upgrade_id = client.schedule_op(&AdminOp::Upgrade(BytesN::from_array(&env, &[0u8; 32])), &200_000);
+    assert_eq!(client.get_pending_ops().len(), 2);
+
+    client.cancel_op(&op_id);
+    client.cancel_op(&upgrade_id);
+    assert_eq!(client.get_pending_ops().len(), 0);
+    assert!(client.try_get_scheduled_op(&op_id).is_err());
+
+    // A cancelled operation can never run
+    env.ledger().with_mut(|li| li.timestamp = 200_000);
+    assert!(client.try_execute_op(&op_id).is_err());
+    assert_eq!(client.get_arbiter(), admin);
+}
+
+#[test]
+fn test_get_solvency_breakdown() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+    run_admin_op(&env, &client, AdminOp::SetFee(1_000));
+    assert_solvent(&client, &token_client.address);
+
+    let agent_id = String::from_str(&env, "test-agent");
+    let job_id = client.create_job(&hirer, &agent_id, &300, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    client.create_job(&hirer, &agent_id, &200, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    client.complete_job(&job_id, &BytesN::from_array(&env, &[1u8; 32]), &token_client.address);
+    assert_solvent(&client, &token_client.address);
+    env.ledger().with_mut(|li| li.timestamp += 86_400);
+    client.finalize(&job_id);
+    assert_solvent(&client, &token_client.address);
+
+    let solvency = client.get_solvency(&token_client.address);
+    assert_eq!(solvency.escrowed, 200);
+    assert_eq!(solvency.claimable, 270);
+    assert_eq!(solvency.fees, 30);
+    assert_eq!(solvency.staked, 0);
+    assert_eq!(solvency.liabilities, 500);
+    assert_eq!(solvency.balance, 500);
+    assert!(solvency.is_solvent);
+
+    // Tokens sent directly to the contract are surplus, not liabilities
+    token_admin_client.mint(&contract_id, &50);
+    let solvency = client.get_solvency(&token_client.address);
+    assert_eq!(solvency.balance, 550);
+    assert_eq!(solvency.liabilities, 500);
+    assert!(solvency.is_solvent);
+}
+
+#[test]
+fn test_legacy_job_records_are_migrated() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let agent_id = String::from_str(&env, "test-agent");
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    let (other_token_client, other_token_admin_client) = create_token_contract(&env, &token_admin);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+
+    // Write jobs in the layout used before storage versioning
+    let legacy_job = |id: u64, status: JobStatus, completed_at: Option<u64>| migration::JobV1 {
+        id,
+        hirer: hirer.clone(),
+        agent_owner: agent_owner.clone(),
+        agent_id: agent_id.clone(),
+        amount: 500,
+        status,
+        created_at: 0,
+        completed_at,
+        results_hash: None,
+    };
+    env.as_contract(&contract_id, || {
+        let storage = env.storage().persistent();
+        storage.set(&DataKey::Job(1), &legacy_job(1, JobStatus::Pending, None));
+        storage.set(&DataKey::Job(2), &legacy_job(2, JobStatus::Completed, Some(100)));
+        storage.set(&DataKey::Job(3), &legacy_job(3, JobStatus::Pending, None));
+    });
+
+    // Version 1 did not record the token, so jobs cannot be read until one is set
+    assert!(client.try_get_job(&1).is_err());
+
+    // Jobs funded in another token are migrated by the admin ahead of time
+    other_token_admin_client.mint(&contract_id, &500);
+    client.migrate_legacy_job(&3, &other_token_client.address);
+    assert_solvent(&client, &other_token_client.address);
+    assert_eq!(client.get_job(&3).token, other_token_client.address);
+
+    // The rest move onto the current layout in the legacy token when read, as
+    // long as the escrow's balance covers the funds they still hold
+    client.set_legacy_token(&token_client.address);
+    assert!(client.try_set_legacy_token(&other_token_client.address).is_err());
+    assert_eq!(client.get_legacy_token(), Some(token_client.address.clone()));
+    token_admin_client.mint(&contract_id, &400);
+    assert!(client.try_get_job(&1).is_err());
+    token_admin_client.mint(&contract_id, &100);
+    let job = client.get_job(&1);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(job.status, JobStatus::Pending);
+    assert_eq!(job.token, token_client.address);
+    assert_eq!(job.release_at, None);
+
+    // Completed jobs were already paid out by version 1
+    let job = client.get_job(&2);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(job.status, JobStatus::Released);
+    assert_eq!(job.release_at, Some(100));
+
+    // Reading again does not backfill twice
+    client.get_job(&1);
+    assert!(client.try_migrate_legacy_job(&1, &token_client.address).is_err());
+    assert_eq!(client.get_open_jobs(&agent_id), 2);
+    assert_eq!(client.get_jobs_by_agent(&agent_id, &None, &0, &10), vec![&env, 3, 1, 2]);
+    assert_eq!(
+        client.get_jobs_by_agent(&agent_id, &Some(JobStatus::Pending), &0, &10),
+        vec![&env, 3, 1]
+    );
+    let record = env.as_contract(&contract_id, || {
+        let record: migration::JobRecord = env.storage().persistent().get(&DataKey::Job(1)).unwrap();
+        record
+    });
+    assert_eq!(record, migration::JobRecord::V2(client.get_job(&1)));
+    assert_eq!(client.get_solvency(&token_client.address).escrowed, 500);
+
+    // The migrated job settles like any other
+    client.complete_job(&1, &BytesN::from_array(&env, &[1u8; 32]), &token_client.address);
+    assert_solvent(&client, &token_client.address);
+    env.ledger().with_mut(|li| li.timestamp = client.get_job(&1).release_at.unwrap());
+    client.finalize(&1);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 500);
+
+    assert_eq!(client.version(), migration::Version { code: 2, schema: 2 });
+}
+
+#[test]
+fn test_spender_allowance() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let spender = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    // Hirer approves the escrow on the token once, then budgets the spender
+    let expiration_ledger = env.ledger().sequence() + 1_000;
+    token_client.approve(&hirer, &contract_id, &1000, &expiration_ledger);
+    client.approve_spender(&hirer, &spender, &token_client.address, &300, &3_600);
+    assert_solvent(&client, &token_client.address);
+
+    let agent_id = String::from_str(&env, "test-agent");
+    let job_id = client.create_job_for(&spender, &hirer, &agent_id, &200, &token_client.address, &None, &None);
+    assert_solvent(&client, &token_client.address);
+
+    let job = client.get_job(&job_id);
+    assert_eq!(job.hirer, hirer);
+    assert_eq!(job.agent_owner, agent_owner);
+    assert_eq!(token_client.balance(&hirer), 800);
+    assert_eq!(
+        client.get_allowance(&hirer, &spender, &token_client.address),
+        Some(Allowance { budget: 300, period: 3_600, period_start: 0, spent: 200 })
+    );
+
+    // The budget is enforced within a period
+    assert!(client
+        .try_create_job_for(&spender, &hirer, &agent_id, &200, &token_client.address, &None, &None)
+        .is_err());
+
+    // A new period restores the budget
+    env.ledger().with_mut(|li| li.timestamp = 3_700);
+    assert_eq!(client.get_allowance(&hirer, &spender, &token_client.address).unwrap().spent, 0);
+
+    // Payment always goes to the agent's current owner in the registry
+    let new_owner = Address::generate(&env);
+    registry.transfer_ownership(&agent_owner, &agent_id, &new_owner);
+    let second_id = client.create_job_for(&spender, &hirer, &agent_id, &200, &token_client.address, &None, &None);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_job(&second_id).agent_owner, new_owner);
+
+    // The hirer still controls jobs opened for them
+    client.cancel_job(&job_id, &token_client.address);
+    assert_eq!(token_client.balance(&hirer), 800);
+    assert_solvent(&client, &token_client.address);
+
+    // Revoking cuts the spender off immediately
+    client.revoke_spender(&hirer, &spender, &token_client.address);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_allowance(&hirer, &spender, &token_client.address), None);
+    assert!(client
+        .try_create_job_for(&spender, &hirer, &agent_id, &50, &token_client.address, &None, &None)
+        .is_err());
+}
+
+#[test]
+fn test_create_job_with_signed_quote() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let other_hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    // Owner registers the key that signs quotes off-chain
+    let agent_id = String::from_str(&env, "test-agent");
+    let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+    registry.set_quote_key(
+        &agent_owner,
+        &agent_id,
+        &BytesN::from_array(&env, &signing_key.verifying_key().to_bytes()),
+    );
+
+    let quote = Quote {
+        agent_id: agent_id.clone(),
+        hirer: hirer.clone(),
+        amount: 750,
+        token: token_client.address.clone(),
+        expiry: 1_000,
+        nonce: 1,
+    };
+    let signature = sign_quote(&env, &contract_id, &signing_key, &quote);
+
+    // Terms that differ from what was signed are rejected
+    let mut tampered = quote.clone();
+    tampered.amount = 1;
+    assert!(client.try_create_job_with_quote(&hirer, &tampered, &signature, &None).is_err());
+    assert!(client.try_create_job_with_quote(&other_hirer, &quote, &signature, &None).is_err());
+
+    let job_id = client.create_job_with_quote(&hirer, &quote, &signature, &None);
+    assert_solvent(&client, &token_client.address);
+
+    let job = client.get_job(&job_id);
+    assert_eq!(job.amount, 750);
+    assert_eq!(job.agent_owner, agent_owner);
+    assert_eq!(token_client.balance(&hirer), 250);
+    assert!(client.is_quote_used(&agent_id, &1));
+
+    // Each nonce can fund only one job
+    assert!(client.try_create_job_with_quote(&hirer, &quote, &signature, &None).is_err());
+
+    // Quotes stop working after their expiry
+    let late_quote = Quote { amount: 100, nonce: 2, ..quote };
+    let late_signature = sign_quote(&env, &contract_id, &signing_key, &late_quote);
+    env.ledger().with_mut(|li| li.timestamp = 1_001);
+    assert!(client.try_create_job_with_quote(&hirer, &late_quote, &late_signature, &None).is_err());
+}
+
+#[test]
+fn test_metered_job_refunds_unused_budget() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &2000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    // Agents without a unit price only take fixed-price jobs
+    let agent_id = String::from_str(&env, "test-agent");
+    assert!(client
+        .try_create_metered_job(&hirer, &agent_id, &1000, &token_client.address, &None)
+        .is_err());
+    registry.set_unit_price(&agent_owner, &agent_id, &30);
+
+    let job_id = client.create_metered_job(&hirer, &agent_id, &1000, &token_client.address, &None);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_job(&job_id).agent_owner, agent_owner);
+
+    // Usage must be reported before the hirer can complete the job
+    let results_hash = BytesN::from_array(&env, &[1u8; 32]);
+    assert!(client.try_complete_job(&job_id, &results_hash, &token_client.address).is_err());
+    client.report_usage(&agent_owner, &job_id, &20);
+    assert_solvent(&client, &token_client.address);
+    assert!(client.try_report_usage(&agent_owner, &job_id, &25).is_err());
+    assert_eq!(
+        client.get_job(&job_id).kind,
+        JobKind::Metered(Metering { unit_price: 30, units: Some(20) })
+    );
+
+    // Settlement pays 20 * 30 and refunds the rest of the budget
+    client.complete_job(&job_id, &results_hash, &token_client.address);
+    assert_solvent(&client, &token_client.address);
+    env.ledger().with_mut(|li| li.timestamp += 86_400);
+    client.finalize(&job_id);
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 600);
+    assert_eq!(client.get_claimable(&hirer, &token_client.address), 400);
+    assert_eq!(token_client.balance(&hirer), 1000);
+    assert_solvent(&client, &token_client.address);
+
+    // Usage beyond the budget is capped at the budget
+    let job_id = client.create_metered_job(&hirer, &agent_id, &500, &token_client.address, &None);
+    assert_solvent(&client, &token_client.address);
+    client.report_usage(&agent_owner, &job_id, &50);
+    assert_solvent(&client, &token_client.address);
+    client.complete_job(&job_id, &results_hash, &token_client.address);
+    assert_solvent(&client, &token_client.address);
+    env.ledger().with_mut(|li| li.timestamp += 86_400);
+    client.finalize(&job_id);
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 1100);
+    assert_eq!(client.get_claimable(&hirer, &token_client.address), 400);
+    assert_solvent(&client, &token_client.address);
+
+    // The hirer withdraws the refund like any other credit
+    client.withdraw(&hirer, &token_client.address, &400);
+    assert_eq!(token_client.balance(&hirer), 900);
+    assert_solvent(&client, &token_client.address);
+}
+
+#[test]
+fn test_streamed_job_withdraw_and_stop() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &2000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &1_000);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    let agent_id = String::from_str(&env, "test-agent");
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    assert!(client
+        .try_create_stream_job(&hirer, &agent_id, &1000, &token_client.address, &2_000, &2_000, &None)
+        .is_err());
+
+    // 1000 streamed over 1000 seconds starting at 2000
+    let job_id = client.create_stream_job(&hirer, &agent_id, &1000, &token_client.address, &2_000, &3_000, &None);
+    assert_solvent(&client, &token_client.address);
+    assert!(client.try_withdraw_stream(&job_id).is_err());
+
+    // Streams settle on their own schedule, not through completion or disputes
+    let results_hash = BytesN::from_array(&env, &[1u8; 32]);
+    assert!(client.try_complete_job(&job_id, &results_hash, &token_client.address).is_err());
+    assert!(client.try_cancel_job(&job_id, &token_client.address).is_err());
+    assert!(client.try_dispute_job(&hirer, &job_id).is_err());
+
+    // A quarter of the way in, the owner is credited 250 less the 10% fee
+    env.ledger().with_mut(|li| li.timestamp = 2_250);
+    assert_eq!(client.withdraw_stream(&job_id), 250);
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 225);
+    assert_eq!(client.get_fee_balance(&token_client.address), 25);
+    assert_solvent(&client, &token_client.address);
+
+    // Stopping halfway credits the next 250 and refunds the unaccrued 500
+    env.ledger().with_mut(|li| li.timestamp = 2_500);
+    let outsider = Address::generate(&env);
+    assert!(client.try_stop_stream(&outsider, &job_id).is_err());
+    client.stop_stream(&hirer, &job_id);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 450);
+    assert_eq!(client.get_claimable(&hirer, &token_client.address), 500);
+    assert_eq!(client.get_fee_balance(&token_client.address), 50);
+
+    let job = client.get_job(&job_id);
+    assert_eq!(job.status, JobStatus::Released);
+    assert_eq!(
+        job.kind,
+        JobKind::Streamed(Stream { start: 2_000, end: 3_000, withdrawn: 500 })
+    );
+    assert_eq!(client.get_open_jobs(&agent_id), 0);
+    assert_solvent(&client, &token_client.address);
+
+    // A stream left to run is released once fully withdrawn
+    let job_id = client.create_stream_job(&hirer, &agent_id, &1000, &token_client.address, &3_000, &4_000, &None);
+    assert_solvent(&client, &token_client.address);
+    env.ledger().with_mut(|li| li.timestamp = 5_000);
+    assert_eq!(client.withdraw_stream(&job_id), 1000);
+    assert_eq!(client.get_job(&job_id).status, JobStatus::Released);
+    assert!(client.try_stop_stream(&hirer, &job_id).is_err());
+    assert_solvent(&client, &token_client.address);
+}
+
+#[test]
+fn test_usd_priced_agent_converted_by_oracle() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1_000_000_000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    // $2.50 per execution
+    let agent_id = String::from_str(&env, "test-agent");
+    let usd = Some(Symbol::new(&env, "USD"));
+    registry.set_price_currency(&agent_owner, &agent_id, &25_000_000, &usd);
+    assert!(client
+        .try_create_job(&hirer, &agent_id, &300_000_000, &token_client.address, &None, &None, &None)
+        .is_err());
+
+    let oracle = MockOracleClient::new(&env, &env.register(MockOracle, ()));
+    run_admin_op(
+        &env,
+        &client,
+        AdminOp::SetPriceOracle(PriceOracle {
+            address: oracle.address.clone(),
+            max_age: 300,
+        }),
+    );
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_price_oracle().unwrap().max_age, 300);
+
+    // At $0.10 per token the job costs 25 tokens, within the hirer's 30 token limit
+    oracle.set_price(&10_000_000_000_000, &env.ledger().timestamp());
+    let client_ref = Some(BytesN::from_array(&env, &[7u8; 32]));
+    let job_id = client.create_job(&hirer, &agent_id, &300_000_000, &token_client.address, &client_ref, &None, &None);
+    assert_eq!(client.get_job(&job_id).amount, 250_000_000);
+    assert_eq!(token_client.balance(&hirer), 750_000_000);
+    assert_solvent(&client, &token_client.address);
+
+    // At $0.08 it would cost 31.25 tokens, beyond the limit
+    oracle.set_price(&8_000_000_000_000, &env.ledger().timestamp());
+    assert!(client
+        .try_create_job(&hirer, &agent_id, &300_000_000, &token_client.address, &None, &None, &None)
+        .is_err());
+
+    // Stale prices are rejected
+    oracle.set_price(&10_000_000_000_000, &env.ledger().timestamp());
+    env.ledger().with_mut(|li| li.timestamp += 301);
+    assert!(client
+        .try_create_job(&hirer, &agent_id, &300_000_000, &token_client.address, &None, &None, &None)
+        .is_err());
+
+    // A retry is answered from the idempotency key without consulting the oracle
+    assert_eq!(
+        client.create_job(&hirer, &agent_id, &300_000_000, &token_client.address, &client_ref, &None, &None),
+        job_id
+    );
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(token_client.balance(&hirer), 750_000_000);
+
+    // Currencies other than the oracle's base cannot be converted
+    oracle.set_price(&10_000_000_000_000, &env.ledger().timestamp());
+    let eur = Some(Symbol::new(&env, "EUR"));
+    registry.set_price_currency(&agent_owner, &agent_id, &25_000_000, &eur);
+    assert!(client
+        .try_create_job(&hirer, &agent_id, &300_000_000, &token_client.address, &None, &None, &None)
+        .is_err());
+}
+
+#[test]
+fn test_token_allowlist() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    let (other_token_client, other_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &2000);
+    other_admin_client.mint(&hirer, &2000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    let agent_id = String::from_str(&env, "test-agent");
+
+    // Nothing is accepted until the admin lists it
+    assert_eq!(client.get_supported_tokens().len(), 0);
+    assert!(client
+        .try_create_job(&hirer, &agent_id, &1000, &token_client.address, &None, &None, &None)
+        .is_err());
+
+    assert!(client.try_add_supported_token(&token_client.address, &-1).is_err());
+    client.add_supported_token(&token_client.address, &500);
+    assert_solvent(&client, &token_client.address);
+    client.add_supported_token(&other_token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(
+        client.get_supported_tokens(),
+        vec![
+            &env,
+            SupportedToken { token: token_client.address.clone(), min_amount: 500 },
+            SupportedToken { token: other_token_client.address.clone(), min_amount: 0 },
+        ]
+    );
+
+    // Jobs below the token minimum are rejected
+    assert!(client
+        .try_create_job(&hirer, &agent_id, &499, &token_client.address, &None, &None, &None)
+        .is_err());
+    let job_id = client.create_job(&hirer, &agent_id, &500, &token_client.address, &None, &None, &None);
+    assert_solvent(&client, &token_client.address);
+
+    // Delisting blocks new jobs but existing ones still settle
+    client.remove_supported_token(&token_client.address);
+    assert_solvent(&client, &token_client.address);
+    assert!(client.try_remove_supported_token(&token_client.address).is_err());
+    assert_eq!(client.get_supported_tokens().len(), 1);
+    assert!(client
+        .try_create_job(&hirer, &agent_id, &500, &token_client.address, &None, &None, &None)
+        .is_err());
+    client.cancel_job(&job_id, &token_client.address);
+    assert_eq!(token_client.balance(&hirer), 2000);
+    assert_solvent(&client, &token_client.address);
+
+    // Listing again only updates the minimum
+    client.add_supported_token(&other_token_client.address, &100);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(
+        client.get_supported_tokens(),
+        vec![&env, SupportedToken { token: other_token_client.address.clone(), min_amount: 100 }]
+    );
+}
+
+#[test]
+fn test_open_tasks_become_jobs() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let other_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &3000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    let agent_id = String::from_str(&env, "test-agent");
+    let other_agent = String::from_str(&env, "other-agent");
+    registry.register_agent(&other_agent, &other_owner, &100, &String::from_str(&env, "ipfs://QmTest123"));
+    let scraping = Symbol::new(&env, "scraping");
+
+    // First come: the first eligible claim is hired on the spot
+    let task_id = client.post_task(&hirer, &1000, &token_client.address, &scraping, &3_600, &ClaimMode::FirstCome);
+    assert_eq!(client.get_open_tasks(), vec![&env, task_id]);
+    assert_solvent(&client, &token_client.address);
+
+    assert!(client.try_claim_task(&agent_owner, &task_id, &agent_id).is_err());
+    registry.set_agent_tags(&agent_owner, &agent_id, &vec![&env, scraping.clone()]);
+    assert!(client.try_claim_task(&other_owner, &task_id, &agent_id).is_err());
+
+    let job_id = client.claim_task(&agent_owner, &task_id, &agent_id).unwrap();
+    assert_solvent(&client, &token_client.address);
+    let task = client.get_task(&task_id);
+    assert_eq!(task.status, TaskStatus::Assigned);
+    assert_eq!(task.job_id, Some(job_id));
+    assert_eq!(client.get_open_tasks().len(), 0);
+    let job = client.get_job(&job_id);
+    assert_eq!((job.agent_owner, job.amount), (agent_owner.clone(), 1000));
+    assert_eq!(token_client.balance(&hirer), 2000);
+    assert_solvent(&client, &token_client.address);
+
+    // Hirer selects: claims queue up until the hirer picks one
+    registry.set_agent_tags(&other_owner, &other_agent, &vec![&env, scraping.clone()]);
+    let task_id = client.post_task(&hirer, &1000, &token_client.address, &scraping, &3_600, &ClaimMode::HirerSelects);
+    assert_eq!(client.claim_task(&agent_owner, &task_id, &agent_id), None);
+    assert_eq!(client.claim_task(&other_owner, &task_id, &other_agent), None);
+    assert_solvent(&client, &token_client.address);
+    assert!(client.try_claim_task(&other_owner, &task_id, &other_agent).is_err());
+    assert_eq!(client.get_task_claims(&task_id).len(), 2);
+
+    let job_id = client.select_claim(&task_id, &other_agent);
+    assert_eq!(client.get_job(&job_id).agent_owner, other_owner);
+    assert!(client.try_select_claim(&task_id, &agent_id).is_err());
+    assert_solvent(&client, &token_client.address);
+
+    // Unclaimed tasks close at the deadline and can be cancelled for a refund
+    let task_id = client.post_task(&hirer, &1000, &token_client.address, &scraping, &3_600, &ClaimMode::FirstCome);
+    assert_solvent(&client, &token_client.address);
+    env.ledger().with_mut(|li| li.timestamp = 3_601);
+    assert!(client.try_claim_task(&agent_owner, &task_id, &agent_id).is_err());
+    client.cancel_task(&task_id);
+    assert_eq!(client.get_task(&task_id).status, TaskStatus::Cancelled);
+    assert_eq!(token_client.balance(&hirer), 1000);
+    assert_solvent(&client, &token_client.address);
+}
+
+#[test]
+fn test_auction_task_hires_winning_bid() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let other_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    let agent_id = String::from_str(&env, "test-agent");
+    let other_agent = String::from_str(&env, "other-agent");
+    registry.register_agent(&other_agent, &other_owner, &100, &String::from_str(&env, "ipfs://QmTest123"));
+    let scraping = Symbol::new(&env, "scraping");
+    registry.set_agent_tags(&agent_owner, &agent_id, &vec![&env, scraping.clone()]);
+    registry.set_agent_tags(&other_owner, &other_agent, &vec![&env, scraping.clone()]);
+
+    // Budget of 1000 with bidding open for an hour
+    let task_id = client.post_task(&hirer, &1000, &token_client.address, &scraping, &3_600, &ClaimMode::Auction);
+    assert_solvent(&client, &token_client.address);
+    assert!(client.try_claim_task(&agent_owner, &task_id, &agent_id).is_err());
+    assert!(client.try_submit_bid(&agent_owner, &task_id, &agent_id, &1001, &7_200).is_err());
+
+    client.submit_bid(&agent_owner, &task_id, &agent_id, &800, &7_200);
+    assert_solvent(&client, &token_client.address);
+    client.submit_bid(&other_owner, &task_id, &other_agent, &700, &10_800);
+    assert_solvent(&client, &token_client.address);
+    client.submit_bid(&agent_owner, &task_id, &agent_id, &650, &9_000);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(
+        client.get_bids(&task_id),
+        vec![
+            &env,
+            Bid { agent_id: agent_id.clone(), price: 650, eta: 9_000, submitted_at: 0 },
+            Bid { agent_id: other_agent.clone(), price: 700, eta: 10_800, submitted_at: 0 },
+        ]
+    );
+
+    // The hirer picks a winner only after bidding closes
+    assert!(client.try_accept_bid(&task_id, &agent_id).is_err());
+    env.ledger().with_mut(|li| li.timestamp = 3_601);
+    assert!(client.try_submit_bid(&other_owner, &task_id, &other_agent, &600, &10_800).is_err());
+
+    // The job is at the bid price and the rest of the budget comes back
+    let job_id = client.accept_bid(&task_id, &agent_id);
+    assert_solvent(&client, &token_client.address);
+    let job = client.get_job(&job_id);
+    assert_eq!((job.agent_owner, job.amount), (agent_owner.clone(), 650));
+    assert_eq!(token_client.balance(&hirer), 350);
+    assert_eq!(client.get_task(&task_id).status, TaskStatus::Assigned);
+    assert!(client.try_accept_bid(&task_id, &other_agent).is_err());
+    assert_solvent(&client, &token_client.address);
+}
+
+#[test]
+fn test_pipeline_unlocks_stages_in_order() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &2000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["scraper", "summarizer", "translator"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    let stage = |agent_id: &str, amount: i128| StageSpec {
+        agent_id: String::from_str(&env, agent_id),
+        amount,
+    };
+    let stages = vec![&env, stage("scraper", 300), stage("summarizer", 200), stage("translator", 100)];
+
+    // Every stage is funded up front
+    let pipeline_id = client.create_pipeline(&hirer, &token_client.address, &stages);
+    assert_solvent(&client, &token_client.address);
+    let jobs = client.get_pipeline(&pipeline_id).jobs;
+    assert_eq!(jobs.len(), 3);
+    assert_eq!(token_client.balance(&hirer), 1400);
+    assert_solvent(&client, &token_client.address);
+
+    let statuses = || {
+        jobs.iter()
+            .map(|job_id| client.get_job(&job_id).status)
+            .collect::<std::vec::Vec<_>>()
+    };
+    assert_eq!(statuses(), [JobStatus::Pending, JobStatus::Locked, JobStatus::Locked]);
+
+    // Later stages cannot be completed before their turn
+    let scraped = BytesN::from_array(&env, &[1u8; 32]);
+    let summary = BytesN::from_array(&env, &[2u8; 32]);
+    assert!(client.try_complete_job(&jobs.get(1).unwrap(), &summary, &token_client.address).is_err());
+    assert!(client.try_cancel_job(&jobs.get(1).unwrap(), &token_client.address).is_err());
+
+    client.complete_job(&jobs.get(0).unwrap(), &scraped, &token_client.address);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(statuses(), [JobStatus::Completed, JobStatus::Pending, JobStatus::Locked]);
+
+    // The second stage's hash commits to the first
+    client.complete_job(&jobs.get(1).unwrap(), &summary, &token_client.address);
+    assert_solvent(&client, &token_client.address);
+    let mut preimage = Bytes::from(scraped);
+    preimage.append(&Bytes::from(summary));
+    let chained: BytesN<32> = env.crypto().sha256(&preimage).into();
+    assert_eq!(client.get_job(&jobs.get(1).unwrap()).results_hash, Some(chained));
+    assert_eq!(statuses(), [JobStatus::Completed, JobStatus::Completed, JobStatus::Pending]);
+
+    // Cancelling refunds only stages that have not started
+    assert!(client.try_cancel_pipeline(&pipeline_id).is_err());
+    let pipeline_id = client.create_pipeline(&hirer, &token_client.address, &stages);
+    assert_solvent(&client, &token_client.address);
+    client.cancel_pipeline(&pipeline_id);
+    assert_solvent(&client, &token_client.address);
+    let jobs = client.get_pipeline(&pipeline_id).jobs;
+    assert_eq!(client.get_job(&jobs.get(0).unwrap()).status, JobStatus::Pending);
+    assert_eq!(client.get_job(&jobs.get(2).unwrap()).status, JobStatus::Cancelled);
+    assert_eq!(token_client.balance(&hirer), 1100);
+    assert_eq!(client.get_open_jobs(&String::from_str(&env, "translator")), 1);
+    assert_solvent(&client, &token_client.address);
+}
+
+#[test]
+fn test_child_jobs_are_funded_from_parent_escrow() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let sub_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["planner"]);
+    let helper_id = String::from_str(&env, "helper");
+    registry.register_agent(&helper_id, &sub_owner, &100, &String::from_str(&env, "ipfs://QmTest123"));
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    let parent_id = client.create_job(
+        &hirer,
+        &String::from_str(&env, "planner"),
+        &1000,
+        &token_client.address,
+        &None,
+        &None,
+        &None,
+    );
+    assert_solvent(&client, &token_client.address);
+
+    // Children can only be funded from what the parent has left
+    assert!(client.try_create_child_job(&agent_owner, &parent_id, &helper_id, &1001).is_err());
+    let first = client.create_child_job(&agent_owner, &parent_id, &helper_id, &300);
+    assert_solvent(&client, &token_client.address);
+    let second = client.create_child_job(&agent_owner, &parent_id, &helper_id, &200);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_job(&parent_id).amount, 500);
+    assert_eq!(client.get_job(&first).hirer, agent_owner);
+    assert_eq!(client.get_job(&first).agent_owner, sub_owner);
+    assert_eq!(client.get_child_jobs(&parent_id), vec![&env, first, second]);
+    assert_eq!(client.get_parent_job(&first), Some(parent_id));
+    assert_eq!(client.get_parent_job(&parent_id), None);
+    assert_eq!(token_client.balance(&hirer), 0);
+    assert_solvent(&client, &token_client.address);
+
+    // A cancelled child's funds go back to the parent, not its hirer
+    client.cancel_job(&second, &token_client.address);
+    assert_eq!(client.get_job(&parent_id).amount, 700);
+    assert_eq!(token_client.balance(&agent_owner), 0);
+    assert_solvent(&client, &token_client.address);
+
+    // The parent waits until every child has settled
+    let results_hash = BytesN::from_array(&env, &[1u8; 32]);
+    client.complete_job(&first, &results_hash, &token_client.address);
+    assert!(env.auths().iter().any(|(address, _)| *address == hirer));
+    assert_solvent(&client, &token_client.address);
+    assert!(client.try_complete_job(&parent_id, &results_hash, &token_client.address).is_err());
+    assert!(client.try_cancel_job(&parent_id, &token_client.address).is_err());
+
+    env.ledger().with_mut(|li| li.timestamp = 86_400);
+    client.finalize(&first);
+    assert_solvent(&client, &token_client.address);
+    client.complete_job(&parent_id, &results_hash, &token_client.address);
+    assert_solvent(&client, &token_client.address);
+    env.ledger().with_mut(|li| li.timestamp = 2 * 86_400);
+    client.finalize(&parent_id);
+    assert_solvent(&client, &token_client.address);
+
+    // The parent's owner keeps whatever the children did not use
+    assert_eq!(client.get_claimable(&sub_owner, &token_client.address), 300);
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 700);
+    assert_solvent(&client, &token_client.address);
+}
+
+#[test]
+fn test_parent_dispute_freezes_child_jobs() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    // The parent's owner subcontracts to another agent of their own
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["planner", "helper"]);
+    let helper_id = String::from_str(&env, "helper");
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    let parent_id = client.create_job(
+        &hirer,
+        &String::from_str(&env, "planner"),
+        &1000,
+        &token_client.address,
+        &None,
+        &None,
+        &None,
+    );
+    assert_solvent(&client, &token_client.address);
+    let approved = client.create_child_job(&agent_owner, &parent_id, &helper_id, &300);
+    assert_solvent(&client, &token_client.address);
+    let contested = client.create_child_job(&agent_owner, &parent_id, &helper_id, &200);
+    assert_solvent(&client, &token_client.address);
+    let pending = client.create_child_job(&agent_owner, &parent_id, &helper_id, &100);
+    assert!(client.try_create_child_job(&agent_owner, &approved, &helper_id, &50).is_err());
+    assert_solvent(&client, &token_client.address);
+
+    // Completing a child needs the parent's hirer as well as its own
+    let results_hash = BytesN::from_array(&env, &[1u8; 32]);
+    client.complete_job(&approved, &results_hash, &token_client.address);
+    assert!(env.auths().iter().any(|(address, _)| *address == hirer));
+    assert_solvent(&client, &token_client.address);
+    client.complete_job(&contested, &results_hash, &token_client.address);
+    assert_solvent(&client, &token_client.address);
+
+    // Disputing the parent calls back the pending child
+    client.dispute_job(&hirer, &parent_id);
+    assert_eq!(client.get_job(&pending).status, JobStatus::Cancelled);
+    assert_eq!(client.get_job(&parent_id).amount, 500);
+    assert_eq!(client.get_open_jobs(&helper_id), 0);
+    assert_solvent(&client, &token_client.address);
+
+    // Completed children are frozen while the parent is in dispute
+    env.ledger().with_mut(|li| li.timestamp = 86_400);
+    assert!(client.try_finalize(&approved).is_err());
+
+    // A child dispute cannot be won by default, since one person may sit on both sides
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    client.dispute_job(&agent_owner, &contested);
+    assert_solvent(&client, &token_client.address);
+    env.ledger().with_mut(|li| li.timestamp = 259_200);
+    assert!(client.try_claim_default(&agent_owner, &contested).is_err());
+
+    // The parent settles once its children are out of dispute
+    assert!(client.try_resolve_dispute(&parent_id, &500).is_err());
+    client.resolve_dispute(&contested, &200);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_job(&parent_id).amount, 700);
+    client.resolve_dispute(&parent_id, &700);
+    assert_eq!(client.get_claimable(&hirer, &token_client.address), 700);
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 0);
+    assert_solvent(&client, &token_client.address);
+
+    // The child the hirer approved is paid once the freeze lifts
+    client.finalize(&approved);
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 300);
+    assert_solvent(&client, &token_client.address);
+}
+
+#[test]
+fn test_pooled_job_funding_and_pro_rata_refunds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let organizer = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&alice, &1000);
+    token_admin_client.mint(&bob, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    let agent_id = String::from_str(&env, "test-agent");
+    let job_id = client.create_pooled_job(&organizer, &agent_id, &900, &token_client.address, &1_000);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_job(&job_id).status, JobStatus::Funding);
+
+    // The job cannot start until the target is reached
+    client.contribute(&alice, &job_id, &300);
+    assert_solvent(&client, &token_client.address);
+    client.contribute(&bob, &job_id, &200);
+    assert_solvent(&client, &token_client.address);
+    let results_hash = BytesN::from_array(&env, &[1u8; 32]);
+    assert!(client.try_complete_job(&job_id, &results_hash, &token_client.address).is_err());
+    assert!(client.try_contribute(&bob, &job_id, &401).is_err());
+    assert_solvent(&client, &token_client.address);
+
+    client.contribute(&bob, &job_id, &400);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_job(&job_id).status, JobStatus::Pending);
+    assert_eq!(
+        client.get_contributions(&job_id),
+        vec![
+            &env,
+            Contribution { contributor: alice.clone(), amount: 300 },
+            Contribution { contributor: bob.clone(), amount: 600 },
+        ]
+    );
+
+    // A partial refund is split by contribution
+    client.dispute_job(&organizer, &job_id);
+    assert_solvent(&client, &token_client.address);
+    client.resolve_dispute(&job_id, &450);
+    assert_eq!(client.get_claimable(&alice, &token_client.address), 150);
+    assert_eq!(client.get_claimable(&bob, &token_client.address), 300);
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 450);
+    assert_eq!(client.get_claimable(&organizer, &token_client.address), 0);
+    assert_solvent(&client, &token_client.address);
+
+    // Cancelling a funded job credits every contributor
+    let job_id = client.create_pooled_job(&organizer, &agent_id, &100, &token_client.address, &1_000);
+    assert_solvent(&client, &token_client.address);
+    client.contribute(&alice, &job_id, &70);
+    assert_solvent(&client, &token_client.address);
+    client.contribute(&bob, &job_id, &30);
+    assert_solvent(&client, &token_client.address);
+    client.cancel_job(&job_id, &token_client.address);
+    assert_eq!(client.get_claimable(&alice, &token_client.address), 220);
+    assert_eq!(client.get_claimable(&bob, &token_client.address), 330);
+    assert_eq!(token_client.balance(&organizer), 0);
+    assert_solvent(&client, &token_client.address);
+
+    // A job that misses its target is refunded in full after the deadline
+    let job_id = client.create_pooled_job(&organizer, &agent_id, &500, &token_client.address, &1_000);
+    assert_solvent(&client, &token_client.address);
+    client.contribute(&alice, &job_id, &100);
+    assert_solvent(&client, &token_client.address);
+    assert!(client.try_refund_pool(&job_id).is_err());
+    env.ledger().with_mut(|li| li.timestamp = 1_001);
+    assert!(client.try_contribute(&bob, &job_id, &400).is_err());
+    client.refund_pool(&job_id);
+    assert_eq!(client.get_job(&job_id).status, JobStatus::Cancelled);
+    assert_eq!(client.get_claimable(&alice, &token_client.address), 320);
+    assert_eq!(client.get_open_jobs(&agent_id), 0);
+    assert_solvent(&client, &token_client.address);
+
+    // Contributors withdraw their refunds themselves
+    client.withdraw(&alice, &token_client.address, &320);
+    assert_eq!(token_client.balance(&alice), 850);
+    assert_solvent(&client, &token_client.address);
+}
+
+#[test]
+fn test_verifier_completes_job_without_hirer() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentEscrowContract, ());
+    let client = AgentEscrowContractClient::new(&env, &contract_id);
+
+    let hirer = Address::generate(&env);
+    let agent_owner = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+
+    let (token_client, token_admin_client) = create_token_contract(&env, &token_admin);
+    token_admin_client.mint(&hirer, &1000);
+
+    let admin = Address::generate(&env);
+    let registry = create_registry_contract(&env, &agent_owner, &["test-agent"]);
+    client.initialize(&admin, &registry.address, &admin, &0);
+    client.add_supported_token(&token_client.address, &0);
+    assert_solvent(&client, &token_client.address);
+
+    let agent_id = String::from_str(&env, "test-agent");
+    let inputs_hash = BytesN::from_array(&env, &[1u8; 32]);
+    let results_hash = BytesN::from_array(&env, &[2u8; 32]);
+    let mut proof = Bytes::from(inputs_hash.clone());
+    proof.append(&Bytes::from(results_hash.clone()));
+
+    // Agents without a verifier still need the hirer
+    let verifier = env.register(MockVerifier, ());
+    assert!(client
+        .try_create_job(&hirer, &agent_id, &100, &token_client.address, &None, &Some(inputs_hash.clone()), &Some(verifier.clone()))
+        .is_err());
+    let unverified =
+        client.create_job(&hirer, &agent_id, &100, &token_client.address, &None, &Some(inputs_hash.clone()), &None);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_job_verifier(&unverified), None);
+
+    // Hirers opt in to the agent's current verifier; without it the job has none
+    registry.set_verifier(&agent_owner, &agent_id, &Some(verifier.clone()));
+    let opted_out =
+        client.create_job(&hirer, &agent_id, &100, &token_client.address, &None, &Some(inputs_hash.clone()), &None);
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_job_verifier(&opted_out), None);
+    assert!(client
+        .try_create_job(&hirer, &agent_id, &100, &token_client.address, &None, &Some(inputs_hash.clone()), &Some(Address::generate(&env)))
+        .is_err());
+    let job_id = client.create_job(
+        &hirer,
+        &agent_id,
+        &100,
+        &token_client.address,
+        &None,
+        &Some(inputs_hash.clone()),
+        &Some(verifier.clone()),
+    );
+    assert_solvent(&client, &token_client.address);
+    assert_eq!(client.get_job_verifier(&job_id), Some(verifier.clone()));
+    assert_eq!(client.get_job_inputs(&job_id), Some(inputs_hash.clone()));
+    assert!(client
+        .try_complete_with_verifier(&unverified, &results_hash, &proof)
+        .is_err());
+    assert!(client
+        .try_complete_with_verifier(&opted_out, &results_hash, &proof)
+        .is_err());
+
+    // The verifier checks against the inputs the hirer recorded, so jobs without them cannot use it
+    let no_inputs =
+        client.create_job(&hirer, &agent_id, &100, &token_client.address, &None, &None, &Some(verifier.clone()));
+    assert_solvent(&client, &token_client.address);
+    assert!(client
+        .try_complete_with_verifier(&no_inputs, &results_hash, &proof)
+        .is_err());
+
+    // Jobs keep the verifier they were hired under
+    registry.set_verifier(&agent_owner, &agent_id, &None);
+
+    // Rejected results leave the job pending
+    let mut bad_proof = Bytes::from_array(&env, &[3u8; 32]);
+    bad_proof.append(&Bytes::from(results_hash.clone()));
+    assert!(client
+        .try_complete_with_verifier(&job_id, &results_hash, &bad_proof)
+        .is_err());
+    assert_eq!(client.get_job(&job_id).status, JobStatus::Pending);
+
+    // Verified results still wait out the challenge window
+    client.complete_with_verifier(&job_id, &results_hash, &proof);
+    assert_solvent(&client, &token_client.address);
+    let job = client.get_job(&job_id);
+    assert_eq!(job.status, JobStatus::Completed);
+    assert_eq!(job.results_hash, Some(results_hash));
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 0);
+    assert_eq!(client.get_open_jobs(&agent_id), 3);
+    assert!(client.try_finalize(&job_id).is_err());
+
+    env.ledger().with_mut(|li| li.timestamp += 86_400);
+    client.finalize(&job_id);
+    assert_eq!(client.get_job(&job_id).status, JobStatus::Released);
+    assert_eq!(client.get_claimable(&agent_owner, &token_client.address), 100);
+    assert_solvent(&client, &token_client.address);
+}
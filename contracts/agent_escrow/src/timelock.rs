@@ -0,0 +1,185 @@
+use soroban_sdk::{contractimpl, contracttype, Address, BytesN, Env, Symbol, Vec};
+
+use crate::jury::JuryConfig;
+use crate::oracle::PriceOracle;
+use crate::{
+    AgentEscrowContract, AgentEscrowContractArgs, AgentEscrowContractClient, DataKey,
+    DEFAULT_MIN_DELAY, MAX_FEE_BPS,
+};
+
+/// Time after its ETA during which a scheduled operation can still run (14 days)
+const GRACE_PERIOD: u64 = 1_209_600;
+
+/// Admin operation that only takes effect after the timelock delay
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AdminOp {
+    SetFee(u32),
+    SetArbiter(Address),
+    SetChallengePeriod(u64),
+    SetResponsePeriod(u64),
+    ConfigureJury(JuryConfig),
+    SetPriceOracle(PriceOracle),
+    SetMinDelay(u64),
+    Upgrade(BytesN<32>),
+}
+
+/// Admin operation waiting in the timelock queue
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScheduledOp {
+    pub id: u64,
+    pub op: AdminOp,
+    pub eta: u64,
+    pub scheduled_at: u64,
+}
+
+#[contractimpl]
+impl AgentEscrowContract {
+    /// Queue an admin operation
+    ///
+    /// # Arguments
+    /// * `op` - Operation to run
+    /// * `eta` - Earliest time it can run; at least the minimum delay from now
+    pub fn schedule_op(env: Env, op: AdminOp, eta: u64) -> u64 {
+        Self::get_admin(env.clone()).require_auth();
+
+        let now = env.ledger().timestamp();
+        assert!(
+            eta >= now + Self::get_min_delay(env.clone()),
+            "ETA is before the minimum delay"
+        );
+        Self::validate_op(&env, &op);
+
+        let op_id: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::OpCounter)
+            .unwrap_or(0)
+            + 1;
+        let scheduled = ScheduledOp {
+            id: op_id,
+            op: op.clone(),
+            eta,
+            scheduled_at: now,
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::ScheduledOp(op_id), &scheduled);
+        env.storage().persistent().set(&DataKey::OpCounter, &op_id);
+
+        let mut pending = Self::get_pending_ops(env.clone());
+        pending.push_back(op_id);
+        env.storage().persistent().set(&DataKey::PendingOps, &pending);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "op_scheduled"), op_id),
+            (op, eta),
+        );
+
+        op_id
+    }
+
+    /// Run a queued operation once its ETA has passed
+    ///
+    /// # Arguments
+    /// * `op_id` - ID returned by `schedule_op`
+    pub fn execute_op(env: Env, op_id: u64) {
+        Self::get_admin(env.clone()).require_auth();
+
+        let scheduled = Self::get_scheduled_op(env.clone(), op_id);
+        let now = env.ledger().timestamp();
+        assert!(now >= scheduled.eta, "Operation is still timelocked");
+        assert!(now <= scheduled.eta + GRACE_PERIOD, "Operation has expired");
+
+        Self::remove_scheduled_op(&env, op_id);
+
+        // Emit event before an upgrade swaps out the running code
+        env.events().publish(
+            (Symbol::new(&env, "op_executed"), op_id),
+            scheduled.op.clone(),
+        );
+
+        Self::apply_op(&env, scheduled.op);
+    }
+
+    /// Drop a queued operation before it runs
+    ///
+    /// # Arguments
+    /// * `op_id` - ID returned by `schedule_op`
+    pub fn cancel_op(env: Env, op_id: u64) {
+        Self::get_admin(env.clone()).require_auth();
+
+        let scheduled = Self::get_scheduled_op(env.clone(), op_id);
+        Self::remove_scheduled_op(&env, op_id);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "op_cancelled"), op_id),
+            scheduled.op,
+        );
+    }
+
+    /// Get a queued operation
+    pub fn get_scheduled_op(env: Env, op_id: u64) -> ScheduledOp {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ScheduledOp(op_id))
+            .expect("Operation not found")
+    }
+
+    /// Get the IDs of all queued operations
+    pub fn get_pending_ops(env: Env) -> Vec<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::PendingOps)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Get the minimum delay between scheduling and running an operation
+    pub fn get_min_delay(env: Env) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::MinDelay)
+            .unwrap_or(DEFAULT_MIN_DELAY)
+    }
+}
+
+impl AgentEscrowContract {
+    fn validate_op(env: &Env, op: &AdminOp) {
+        match op {
+            AdminOp::SetFee(fee_bps) => assert!(*fee_bps <= MAX_FEE_BPS, "Fee too high"),
+            AdminOp::ConfigureJury(config) => Self::validate_jury_config(env, config),
+            AdminOp::SetPriceOracle(oracle) => Self::validate_price_oracle(oracle),
+            _ => {}
+        }
+    }
+
+    fn apply_op(env: &Env, op: AdminOp) {
+        // Re-check in case state changed while the operation was queued
+        Self::validate_op(env, &op);
+
+        let storage = env.storage().persistent();
+        match op {
+            AdminOp::SetFee(fee_bps) => storage.set(&DataKey::FeeBps, &fee_bps),
+            AdminOp::SetArbiter(arbiter) => storage.set(&DataKey::Arbiter, &arbiter),
+            AdminOp::SetChallengePeriod(period) => storage.set(&DataKey::ChallengePeriod, &period),
+            AdminOp::SetResponsePeriod(period) => storage.set(&DataKey::ResponsePeriod, &period),
+            AdminOp::ConfigureJury(config) => storage.set(&DataKey::JuryConfig, &config),
+            AdminOp::SetPriceOracle(oracle) => storage.set(&DataKey::PriceOracle, &oracle),
+            AdminOp::SetMinDelay(delay) => storage.set(&DataKey::MinDelay, &delay),
+            AdminOp::Upgrade(wasm_hash) => env.deployer().update_current_contract_wasm(wasm_hash),
+        }
+    }
+
+    fn remove_scheduled_op(env: &Env, op_id: u64) {
+        env.storage().persistent().remove(&DataKey::ScheduledOp(op_id));
+
+        let mut pending = Self::get_pending_ops(env.clone());
+        if let Some(index) = pending.first_index_of(op_id) {
+            pending.remove(index);
+        }
+        env.storage().persistent().set(&DataKey::PendingOps, &pending);
+    }
+}
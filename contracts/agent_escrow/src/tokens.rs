@@ -0,0 +1,98 @@
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Symbol, Vec};
+
+use crate::{AgentEscrowContract, AgentEscrowContractArgs, AgentEscrowContractClient, DataKey};
+
+/// Token the escrow accepts for jobs
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SupportedToken {
+    pub token: Address,
+    pub min_amount: i128,
+}
+
+#[contractimpl]
+impl AgentEscrowContract {
+    /// Accept a token for new jobs, or change its minimum job amount
+    ///
+    /// # Arguments
+    /// * `token` - Token contract address
+    /// * `min_amount` - Smallest job amount accepted in this token (0 = no minimum)
+    pub fn add_supported_token(env: Env, token: Address, min_amount: i128) {
+        Self::get_admin(env.clone()).require_auth();
+
+        assert!(min_amount >= 0, "Minimum amount cannot be negative");
+
+        let key = DataKey::TokenMinAmount(token.clone());
+        if !env.storage().persistent().has(&key) {
+            let mut tokens = Self::supported_token_list(&env);
+            tokens.push_back(token.clone());
+            env.storage().persistent().set(&DataKey::SupportedTokens, &tokens);
+        }
+        env.storage().persistent().set(&key, &min_amount);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "token_supported"), token),
+            min_amount,
+        );
+    }
+
+    /// Stop accepting a token for new jobs
+    ///
+    /// Jobs already funded in the token still settle normally.
+    ///
+    /// # Arguments
+    /// * `token` - Token contract address
+    pub fn remove_supported_token(env: Env, token: Address) {
+        Self::get_admin(env.clone()).require_auth();
+
+        let key = DataKey::TokenMinAmount(token.clone());
+        assert!(env.storage().persistent().has(&key), "Token not supported");
+        env.storage().persistent().remove(&key);
+
+        let mut tokens = Self::supported_token_list(&env);
+        if let Some(index) = tokens.first_index_of(&token) {
+            tokens.remove(index);
+        }
+        env.storage().persistent().set(&DataKey::SupportedTokens, &tokens);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "token_removed"), token),
+            (),
+        );
+    }
+
+    /// Get every token accepted for new jobs with its minimum job amount
+    pub fn get_supported_tokens(env: Env) -> Vec<SupportedToken> {
+        let mut supported = Vec::new(&env);
+        for token in Self::supported_token_list(&env) {
+            let min_amount: i128 = env
+                .storage()
+                .persistent()
+                .get(&DataKey::TokenMinAmount(token.clone()))
+                .unwrap_or(0);
+            supported.push_back(SupportedToken { token, min_amount });
+        }
+        supported
+    }
+}
+
+impl AgentEscrowContract {
+    /// Check a new job is paid in an allowed token and meets its minimum
+    pub(crate) fn require_supported_token(env: &Env, token: &Address, amount: i128) {
+        let min_amount: i128 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::TokenMinAmount(token.clone()))
+            .expect("Token not supported");
+        assert!(amount >= min_amount, "Amount below token minimum");
+    }
+
+    fn supported_token_list(env: &Env) -> Vec<Address> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::SupportedTokens)
+            .unwrap_or(Vec::new(env))
+    }
+}
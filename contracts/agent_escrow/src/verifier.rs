@@ -0,0 +1,94 @@
+use soroban_sdk::{contractclient, contractimpl, Address, Bytes, BytesN, Env, String, Symbol};
+
+use crate::{AgentEscrowContract, AgentEscrowContractArgs, AgentEscrowContractClient, DataKey, JobStatus};
+
+/// Interface an agent's result verifier contract must implement
+#[contractclient(name = "VerifierClient")]
+pub trait ResultVerifierInterface {
+    fn verify(
+        env: Env,
+        job_id: u64,
+        inputs_hash: BytesN<32>,
+        results_hash: BytesN<32>,
+        proof: Bytes,
+    ) -> bool;
+}
+
+#[contractimpl]
+impl AgentEscrowContract {
+    /// Complete a job once the verifier its hirer accepted approves the results
+    ///
+    /// Anyone may call this, so a verified agent does not wait on the hirer.
+    /// The payment is still held for the challenge window, during which the
+    /// hirer can dispute, and `finalize` releases it. The verifier is given
+    /// the inputs hash the hirer recorded when creating the job.
+    ///
+    /// # Arguments
+    /// * `job_id` - ID of the pending job
+    /// * `results_hash` - Hash of the execution results
+    /// * `proof` - Verifier-specific evidence that the results are correct
+    pub fn complete_with_verifier(env: Env, job_id: u64, results_hash: BytesN<32>, proof: Bytes) {
+        Self::require_not_paused(&env);
+
+        let mut job = Self::load_job(&env, job_id);
+        assert!(job.status == JobStatus::Pending, "Job is not pending");
+        Self::require_children_settled(&env, job_id);
+        Self::require_parent_approval(&env, &job);
+
+        let verifier = Self::get_job_verifier(env.clone(), job_id).expect("Job has no verifier");
+        let inputs_hash = Self::get_job_inputs(env.clone(), job_id).expect("Job has no inputs hash");
+        let verified = VerifierClient::new(&env, &verifier).verify(
+            &job_id,
+            &inputs_hash,
+            &results_hash,
+            &proof,
+        );
+        assert!(verified, "Results failed verification");
+
+        Self::mark_completed(&env, &mut job, results_hash);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "job_verified"), job_id),
+            (verifier, inputs_hash),
+        );
+    }
+
+    /// Get the verifier a job was created with (`None` if the hirer accepted none)
+    pub fn get_job_verifier(env: Env, job_id: u64) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::JobVerifier(job_id))
+    }
+
+    /// Get the inputs hash the hirer recorded for a job (`None` if there was none)
+    pub fn get_job_inputs(env: Env, job_id: u64) -> Option<BytesN<32>> {
+        env.storage().persistent().get(&DataKey::JobInputs(job_id))
+    }
+}
+
+impl AgentEscrowContract {
+    /// Record a job's inputs hash and the verifier its hirer accepted
+    ///
+    /// The job keeps that verifier even if the agent's changes later.
+    pub(crate) fn record_verification(
+        env: &Env,
+        job_id: u64,
+        agent_id: &String,
+        inputs_hash: Option<BytesN<32>>,
+        expected_verifier: Option<Address>,
+    ) {
+        if let Some(verifier) = expected_verifier {
+            assert!(
+                Self::registry_client(env).get_verifier(agent_id) == Some(verifier.clone()),
+                "Verifier does not match the agent's"
+            );
+            env.storage()
+                .persistent()
+                .set(&DataKey::JobVerifier(job_id), &verifier);
+        }
+        if let Some(inputs_hash) = inputs_hash {
+            env.storage()
+                .persistent()
+                .set(&DataKey::JobInputs(job_id), &inputs_hash);
+        }
+    }
+}
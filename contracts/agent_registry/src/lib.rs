@@ -1,6 +1,20 @@
 #![no_std]
 
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, String, Symbol, Vec};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, Address, BytesN, Env, String, Symbol, TryFromVal, Val,
+    Vec,
+};
+
+pub mod org;
+pub mod timelock;
+
+use org::Role;
+
+/// Version of the contract code, bumped on every release
+const CODE_VERSION: u32 = 2;
+
+/// Version of the storage layout written by this code
+const SCHEMA_VERSION: u32 = 2;
 
 /// Agent metadata structure
 #[contracttype]
@@ -12,6 +26,57 @@ pub struct AgentInfo {
     pub metadata_uri: String,
     pub is_active: bool,
     pub registered_at: u64,
+    pub max_open_jobs: u32,
+    pub org_id: Option<String>,
+    pub unit_price: i128,
+    pub price_currency: Option<Symbol>,
+}
+
+/// Agent layout before storage versioning (schema 1)
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AgentInfoV1 {
+    pub agent_id: String,
+    pub owner: Address,
+    pub price: i128,
+    pub metadata_uri: String,
+    pub is_active: bool,
+    pub registered_at: u64,
+}
+
+impl From<AgentInfoV1> for AgentInfo {
+    fn from(old: AgentInfoV1) -> Self {
+        AgentInfo {
+            agent_id: old.agent_id,
+            owner: old.owner,
+            price: old.price,
+            metadata_uri: old.metadata_uri,
+            is_active: old.is_active,
+            registered_at: old.registered_at,
+            max_open_jobs: 0,
+            org_id: None,
+            unit_price: 0,
+            price_currency: None,
+        }
+    }
+}
+
+/// Versioned agent record as stored under `DataKey::Agent`
+/// 
+/// Older versions are upgraded to the newest variant when read.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AgentRecord {
+    V1(AgentInfoV1),
+    V2(AgentInfo),
+}
+
+/// Code and storage schema versions
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Version {
+    pub code: u32,
+    pub schema: u32,
 }
 
 /// Storage keys
@@ -20,6 +85,16 @@ pub enum DataKey {
     Agent(String),
     AgentsByOwner(Address),
     AllAgents,
+    Admin,
+    Paused,
+    Org(String),
+    OrgMembers(String),
+    OrgRole(String, Address),
+    AgentsByOrg(String),
+    QuoteKey(String),
+    AgentTags(String),
+    Verifier(String),
+    ScheduledUpgrade,
 }
 
 #[contract]
@@ -27,6 +102,57 @@ pub struct AgentRegistryContract;
 
 #[contractimpl]
 impl AgentRegistryContract {
+    /// Initialize the contract
+    /// 
+    /// # Arguments
+    /// * `admin` - Address allowed to pause the registry
+    pub fn initialize(env: Env, admin: Address) {
+        assert!(
+            !env.storage().persistent().has(&DataKey::Admin),
+            "Already initialized"
+        );
+
+        env.storage().persistent().set(&DataKey::Admin, &admin);
+    }
+
+    /// Pause state-changing entry points
+    /// 
+    /// `deactivate_agent` stays available so owners can always take an agent offline.
+    pub fn pause(env: Env) {
+        let admin = Self::get_admin(env.clone());
+        admin.require_auth();
+
+        env.storage().persistent().set(&DataKey::Paused, &true);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "contract_paused"), admin),
+            (),
+        );
+    }
+
+    /// Resume normal operation after a pause
+    pub fn unpause(env: Env) {
+        let admin = Self::get_admin(env.clone());
+        admin.require_auth();
+
+        env.storage().persistent().set(&DataKey::Paused, &false);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "contract_unpaused"), admin),
+            (),
+        );
+    }
+
+    /// Whether the contract is paused
+    pub fn is_paused(env: Env) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Paused)
+            .unwrap_or(false)
+    }
+
     /// Register a new agent on the blockchain
     /// 
     /// # Arguments
@@ -41,17 +167,11 @@ impl AgentRegistryContract {
         price: i128,
         metadata_uri: String,
     ) {
+        Self::require_not_paused(&env);
+
         // Verify the owner is the caller
         owner.require_auth();
 
-        // Check if agent already exists
-        let existing: Option<AgentInfo> = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Agent(agent_id.clone()));
-        
-        assert!(existing.is_none(), "Agent already registered");
-
         // Create agent info
         let agent_info = AgentInfo {
             agent_id: agent_id.clone(),
@@ -60,18 +180,14 @@ impl AgentRegistryContract {
             metadata_uri: metadata_uri.clone(),
             is_active: true,
             registered_at: env.ledger().timestamp(),
+            max_open_jobs: 0,
+            org_id: None,
+            unit_price: 0,
+            price_currency: None,
         };
 
-        // Store agent
-        env.storage()
-            .persistent()
-            .set(&DataKey::Agent(agent_id.clone()), &agent_info);
-
-        // Add to owner's agents list
-        Self::add_to_owner_agents(&env, &owner, agent_id.clone());
-
-        // Add to all agents list
-        Self::add_to_all_agents(&env, agent_id.clone());
+        // Store agent and update indexes
+        Self::store_new_agent(&env, &agent_info);
 
         // Emit event
         env.events().publish(
@@ -83,25 +199,22 @@ impl AgentRegistryContract {
     /// Update the price of an agent
     /// 
     /// # Arguments
+    /// * `caller` - Agent owner, or an org admin or maintainer for organization agents
     /// * `agent_id` - ID of the agent to update
     /// * `new_price` - New price per execution
-    pub fn update_agent_price(env: Env, agent_id: String, new_price: i128) {
-        let mut agent_info: AgentInfo = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Agent(agent_id.clone()))
-            .expect("Agent not found");
+    pub fn update_agent_price(env: Env, caller: Address, agent_id: String, new_price: i128) {
+        Self::require_not_paused(&env);
+
+        let mut agent_info = Self::load_agent(&env, &agent_id);
 
-        // Only owner can update price
-        agent_info.owner.require_auth();
+        // Only owner or org maintainers can update price
+        Self::require_agent_role(&env, &caller, &agent_info, &[Role::Admin, Role::Maintainer]);
 
         let old_price = agent_info.price;
         agent_info.price = new_price;
 
         // Save updated agent
-        env.storage()
-            .persistent()
-            .set(&DataKey::Agent(agent_id.clone()), &agent_info);
+        Self::save_agent(&env, &agent_info);
 
         // Emit event
         env.events().publish(
@@ -110,33 +223,211 @@ impl AgentRegistryContract {
         );
     }
 
+    /// Price an agent in another currency, converted by the escrow's price oracle
+    /// 
+    /// # Arguments
+    /// * `caller` - Agent owner, or an org admin or maintainer for organization agents
+    /// * `agent_id` - ID of the agent to update
+    /// * `price` - New price per execution, with 7 decimals in the quote currency
+    /// * `currency` - Quote currency such as `USD` (`None` = price in stroops of the payment token)
+    pub fn set_price_currency(
+        env: Env,
+        caller: Address,
+        agent_id: String,
+        price: i128,
+        currency: Option<Symbol>,
+    ) {
+        Self::require_not_paused(&env);
+
+        let mut agent_info = Self::load_agent(&env, &agent_id);
+
+        // Only owner or org maintainers can update price
+        Self::require_agent_role(&env, &caller, &agent_info, &[Role::Admin, Role::Maintainer]);
+
+        // Price and currency change together so the price is never misread
+        agent_info.price = price;
+        agent_info.price_currency = currency.clone();
+
+        // Save updated agent
+        Self::save_agent(&env, &agent_info);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "price_currency_updated"), agent_id),
+            (price, currency),
+        );
+    }
+
+    /// Limit how many jobs an agent accepts at once
+    /// 
+    /// # Arguments
+    /// * `caller` - Agent owner, or an org admin or maintainer for organization agents
+    /// * `agent_id` - ID of the agent to update
+    /// * `max_open_jobs` - Maximum number of open escrow jobs (0 = unlimited)
+    pub fn set_max_open_jobs(env: Env, caller: Address, agent_id: String, max_open_jobs: u32) {
+        Self::require_not_paused(&env);
+
+        let mut agent_info = Self::load_agent(&env, &agent_id);
+
+        // Only owner or org maintainers can change capacity
+        Self::require_agent_role(&env, &caller, &agent_info, &[Role::Admin, Role::Maintainer]);
+
+        agent_info.max_open_jobs = max_open_jobs;
+
+        // Save updated agent
+        Self::save_agent(&env, &agent_info);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "capacity_updated"), agent_id),
+            max_open_jobs,
+        );
+    }
+
+    /// Set the price per unit of usage for metered jobs
+    /// 
+    /// # Arguments
+    /// * `caller` - Agent owner, or an org admin or maintainer for organization agents
+    /// * `agent_id` - ID of the agent to update
+    /// * `unit_price` - Price per reported unit in stroops (0 = metered jobs not offered)
+    pub fn set_unit_price(env: Env, caller: Address, agent_id: String, unit_price: i128) {
+        Self::require_not_paused(&env);
+
+        let mut agent_info = Self::load_agent(&env, &agent_id);
+
+        // Only owner or org maintainers can update pricing
+        Self::require_agent_role(&env, &caller, &agent_info, &[Role::Admin, Role::Maintainer]);
+
+        assert!(unit_price >= 0, "Unit price cannot be negative");
+        agent_info.unit_price = unit_price;
+
+        // Save updated agent
+        Self::save_agent(&env, &agent_info);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "unit_price_updated"), agent_id),
+            unit_price,
+        );
+    }
+
+    /// Register the ed25519 public key that signs the agent's price quotes
+    /// 
+    /// # Arguments
+    /// * `caller` - Agent owner, or an org admin or maintainer for organization agents
+    /// * `agent_id` - ID of the agent to update
+    /// * `quote_key` - Ed25519 public key used to sign quotes off-chain
+    pub fn set_quote_key(env: Env, caller: Address, agent_id: String, quote_key: BytesN<32>) {
+        Self::require_not_paused(&env);
+
+        let agent_info = Self::load_agent(&env, &agent_id);
+
+        // Only owner or org maintainers can set the quote key
+        Self::require_agent_role(&env, &caller, &agent_info, &[Role::Admin, Role::Maintainer]);
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::QuoteKey(agent_id.clone()), &quote_key);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "quote_key_set"), agent_id),
+            quote_key,
+        );
+    }
+
+    /// Set the capability tags an agent advertises (e.g. `scraping`, `summarize`)
+    /// 
+    /// Tags decide which open tasks in the escrow the agent can claim.
+    /// 
+    /// # Arguments
+    /// * `caller` - Agent owner, or an org admin or maintainer for organization agents
+    /// * `agent_id` - ID of the agent to update
+    /// * `tags` - Complete list of tags, replacing any previous ones
+    pub fn set_agent_tags(env: Env, caller: Address, agent_id: String, tags: Vec<Symbol>) {
+        Self::require_not_paused(&env);
+
+        let agent_info = Self::load_agent(&env, &agent_id);
+
+        // Only owner or org maintainers can change tags
+        Self::require_agent_role(&env, &caller, &agent_info, &[Role::Admin, Role::Maintainer]);
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::AgentTags(agent_id.clone()), &tags);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "tags_updated"), agent_id),
+            tags,
+        );
+    }
+
+    /// Set or clear the contract that checks an agent's results
+    /// 
+    /// The escrow calls the verifier's `verify` to release payment for the
+    /// agent's jobs without the hirer. Jobs keep the verifier they were
+    /// created with.
+    /// 
+    /// # Arguments
+    /// * `caller` - Agent owner, or an org admin or maintainer for organization agents
+    /// * `agent_id` - ID of the agent to update
+    /// * `verifier` - Verifier contract address, or `None` to remove it
+    pub fn set_verifier(env: Env, caller: Address, agent_id: String, verifier: Option<Address>) {
+        Self::require_not_paused(&env);
+
+        let agent_info = Self::load_agent(&env, &agent_id);
+
+        // Only owner or org maintainers can set the verifier
+        Self::require_agent_role(&env, &caller, &agent_info, &[Role::Admin, Role::Maintainer]);
+
+        let key = DataKey::Verifier(agent_id.clone());
+        match &verifier {
+            Some(address) => env.storage().persistent().set(&key, address),
+            None => env.storage().persistent().remove(&key),
+        }
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "verifier_updated"), agent_id),
+            verifier,
+        );
+    }
+
     /// Transfer ownership of an agent
     /// 
+    /// Organization agents leave the organization and become owned by `new_owner`.
+    /// 
     /// # Arguments
+    /// * `caller` - Agent owner, or an org admin for organization agents
     /// * `agent_id` - ID of the agent to transfer
     /// * `new_owner` - Address of the new owner
-    pub fn transfer_ownership(env: Env, agent_id: String, new_owner: Address) {
-        let mut agent_info: AgentInfo = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Agent(agent_id.clone()))
-            .expect("Agent not found");
+    pub fn transfer_ownership(env: Env, caller: Address, agent_id: String, new_owner: Address) {
+        Self::require_not_paused(&env);
 
-        // Only current owner can transfer
-        agent_info.owner.require_auth();
+        let mut agent_info = Self::load_agent(&env, &agent_id);
+
+        // Only current owner or org admins can transfer
+        Self::require_agent_role(&env, &caller, &agent_info, &[Role::Admin]);
 
         let old_owner = agent_info.owner.clone();
         agent_info.owner = new_owner.clone();
+        if let Some(org_id) = agent_info.org_id.take() {
+            Self::remove_from_org_agents(&env, &org_id, agent_id.clone());
+        }
 
         // Update storage
-        env.storage()
-            .persistent()
-            .set(&DataKey::Agent(agent_id.clone()), &agent_info);
+        Self::save_agent(&env, &agent_info);
 
         // Update ownership indexes
         Self::remove_from_owner_agents(&env, &old_owner, agent_id.clone());
         Self::add_to_owner_agents(&env, &new_owner, agent_id.clone());
 
+        // Quotes signed for the previous owner must not bind the new one
+        env.storage()
+            .persistent()
+            .remove(&DataKey::QuoteKey(agent_id.clone()));
+
         // Emit event
         env.events().publish(
             (Symbol::new(&env, "ownership_transferred"), agent_id),
@@ -147,23 +438,18 @@ impl AgentRegistryContract {
     /// Deactivate an agent (soft delete)
     /// 
     /// # Arguments
+    /// * `caller` - Agent owner, or an org admin or maintainer for organization agents
     /// * `agent_id` - ID of the agent to deactivate
-    pub fn deactivate_agent(env: Env, agent_id: String) {
-        let mut agent_info: AgentInfo = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Agent(agent_id.clone()))
-            .expect("Agent not found");
+    pub fn deactivate_agent(env: Env, caller: Address, agent_id: String) {
+        let mut agent_info = Self::load_agent(&env, &agent_id);
 
-        // Only owner can deactivate
-        agent_info.owner.require_auth();
+        // Only owner or org maintainers can deactivate
+        Self::require_agent_role(&env, &caller, &agent_info, &[Role::Admin, Role::Maintainer]);
 
         agent_info.is_active = false;
 
         // Save updated agent
-        env.storage()
-            .persistent()
-            .set(&DataKey::Agent(agent_id.clone()), &agent_info);
+        Self::save_agent(&env, &agent_info);
 
         // Emit event
         env.events().publish(
@@ -175,23 +461,20 @@ impl AgentRegistryContract {
     /// Reactivate a deactivated agent
     /// 
     /// # Arguments
+    /// * `caller` - Agent owner, or an org admin or maintainer for organization agents
     /// * `agent_id` - ID of the agent to reactivate
-    pub fn activate_agent(env: Env, agent_id: String) {
-        let mut agent_info: AgentInfo = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Agent(agent_id.clone()))
-            .expect("Agent not found");
+    pub fn activate_agent(env: Env, caller: Address, agent_id: String) {
+        Self::require_not_paused(&env);
 
-        // Only owner can activate
-        agent_info.owner.require_auth();
+        let mut agent_info = Self::load_agent(&env, &agent_id);
+
+        // Only owner or org maintainers can activate
+        Self::require_agent_role(&env, &caller, &agent_info, &[Role::Admin, Role::Maintainer]);
 
         agent_info.is_active = true;
 
         // Save updated agent
-        env.storage()
-            .persistent()
-            .set(&DataKey::Agent(agent_id.clone()), &agent_info);
+        Self::save_agent(&env, &agent_info);
 
         // Emit event
         env.events().publish(
@@ -200,15 +483,49 @@ impl AgentRegistryContract {
         );
     }
 
+    /// Get the contract admin
+    pub fn get_admin(env: Env) -> Address {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Admin)
+            .expect("Not initialized")
+    }
+
     /// Get agent information
     /// 
     /// # Arguments
     /// * `agent_id` - ID of the agent to retrieve
     pub fn get_agent(env: Env, agent_id: String) -> AgentInfo {
+        Self::load_agent(&env, &agent_id)
+    }
+
+    /// Get the key that signs an agent's price quotes (`None` if not set)
+    /// 
+    /// # Arguments
+    /// * `agent_id` - ID of the agent
+    pub fn get_quote_key(env: Env, agent_id: String) -> Option<BytesN<32>> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::QuoteKey(agent_id))
+    }
+
+    /// Get the capability tags of an agent
+    /// 
+    /// # Arguments
+    /// * `agent_id` - ID of the agent
+    pub fn get_agent_tags(env: Env, agent_id: String) -> Vec<Symbol> {
         env.storage()
             .persistent()
-            .get(&DataKey::Agent(agent_id))
-            .expect("Agent not found")
+            .get(&DataKey::AgentTags(agent_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Get the contract that verifies an agent's results (`None` if not set)
+    /// 
+    /// # Arguments
+    /// * `agent_id` - ID of the agent
+    pub fn get_verifier(env: Env, agent_id: String) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::Verifier(agent_id))
     }
 
     /// Get all agents owned by a specific address
@@ -230,8 +547,67 @@ impl AgentRegistryContract {
             .unwrap_or(Vec::new(&env))
     }
 
+    /// Get the code version and the storage schema version it writes
+    pub fn version(_env: Env) -> Version {
+        Version {
+            code: CODE_VERSION,
+            schema: SCHEMA_VERSION,
+        }
+    }
+
     // Internal helper functions
 
+    fn require_not_paused(env: &Env) {
+        assert!(!Self::is_paused(env.clone()), "Contract is paused");
+    }
+
+    /// Read an agent, upgrading records written by older schema versions
+    fn load_agent(env: &Env, agent_id: &String) -> AgentInfo {
+        let key = DataKey::Agent(agent_id.clone());
+        let raw: Val = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .expect("Agent not found");
+
+        // Records written before versioning are bare structs rather than enums
+        let record = if Vec::<Val>::try_from_val(env, &raw).is_ok() {
+            AgentRecord::try_from_val(env, &raw).expect("Invalid agent record")
+        } else {
+            AgentRecord::V1(AgentInfoV1::try_from_val(env, &raw).expect("Invalid agent record"))
+        };
+
+        let agent_info: AgentInfo = match record {
+            AgentRecord::V2(agent_info) => return agent_info,
+            AgentRecord::V1(old) => old.into(),
+        };
+        Self::save_agent(env, &agent_info);
+        agent_info
+    }
+
+    fn save_agent(env: &Env, agent_info: &AgentInfo) {
+        env.storage().persistent().set(
+            &DataKey::Agent(agent_info.agent_id.clone()),
+            &AgentRecord::V2(agent_info.clone()),
+        );
+    }
+
+    fn store_new_agent(env: &Env, agent_info: &AgentInfo) {
+        // Check if agent already exists
+        assert!(
+            !env.storage().persistent().has(&DataKey::Agent(agent_info.agent_id.clone())),
+            "Agent already registered"
+        );
+
+        Self::save_agent(env, agent_info);
+
+        // Add to owner's agents list
+        Self::add_to_owner_agents(env, &agent_info.owner, agent_info.agent_id.clone());
+
+        // Add to all agents list
+        Self::add_to_all_agents(env, agent_info.agent_id.clone());
+    }
+
     fn add_to_owner_agents(env: &Env, owner: &Address, agent_id: String) {
         let mut agents: Vec<String> = env
             .storage()
@@ -246,7 +622,7 @@ impl AgentRegistryContract {
     }
 
     fn remove_from_owner_agents(env: &Env, owner: &Address, agent_id: String) {
-        let mut agents: Vec<String> = env
+        let agents: Vec<String> = env
             .storage()
             .persistent()
             .get(&DataKey::AgentsByOwner(owner.clone()))
@@ -0,0 +1,305 @@
+use soroban_sdk::{contractimpl, contracttype, Address, Env, String, Symbol, Vec};
+
+use crate::{AgentInfo, AgentRegistryContract, AgentRegistryContractArgs, AgentRegistryContractClient, DataKey};
+
+/// Role of an organization member
+///
+/// Admins manage members and can do everything; maintainers manage agent
+/// listings; finance manages where agent payouts go.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Role {
+    Admin,
+    Maintainer,
+    Finance,
+}
+
+/// Organization that can own agents
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Organization {
+    pub org_id: String,
+    pub created_by: Address,
+    pub created_at: u64,
+}
+
+#[contractimpl]
+impl AgentRegistryContract {
+    /// Create an organization with the creator as its first admin
+    ///
+    /// # Arguments
+    /// * `creator` - Address creating the organization
+    /// * `org_id` - Unique identifier for the organization
+    pub fn create_org(env: Env, creator: Address, org_id: String) {
+        Self::require_not_paused(&env);
+
+        creator.require_auth();
+
+        assert!(
+            !env.storage().persistent().has(&DataKey::Org(org_id.clone())),
+            "Organization already exists"
+        );
+
+        let org = Organization {
+            org_id: org_id.clone(),
+            created_by: creator.clone(),
+            created_at: env.ledger().timestamp(),
+        };
+        env.storage().persistent().set(&DataKey::Org(org_id.clone()), &org);
+        Self::store_member_role(&env, &org_id, &creator, Role::Admin);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "org_created"), org_id),
+            creator,
+        );
+    }
+
+    /// Add a member to an organization or change their role
+    ///
+    /// # Arguments
+    /// * `caller` - Admin of the organization
+    /// * `org_id` - ID of the organization
+    /// * `member` - Address of the member
+    /// * `role` - Role to give the member
+    pub fn set_member_role(env: Env, caller: Address, org_id: String, member: Address, role: Role) {
+        Self::require_not_paused(&env);
+
+        Self::require_org_role(&env, &caller, &org_id, &[Role::Admin]);
+
+        if role != Role::Admin {
+            Self::require_other_admin(&env, &org_id, &member);
+        }
+        Self::store_member_role(&env, &org_id, &member, role);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "member_role_set"), org_id),
+            (member, role),
+        );
+    }
+
+    /// Remove a member from an organization
+    ///
+    /// # Arguments
+    /// * `caller` - Admin of the organization
+    /// * `org_id` - ID of the organization
+    /// * `member` - Address of the member to remove
+    pub fn remove_member(env: Env, caller: Address, org_id: String, member: Address) {
+        Self::require_not_paused(&env);
+
+        Self::require_org_role(&env, &caller, &org_id, &[Role::Admin]);
+        assert!(
+            Self::get_member_role(env.clone(), org_id.clone(), member.clone()).is_some(),
+            "Not a member"
+        );
+        Self::require_other_admin(&env, &org_id, &member);
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::OrgRole(org_id.clone(), member.clone()));
+        let mut members = Self::get_org_members(env.clone(), org_id.clone());
+        if let Some(index) = members.first_index_of(&member) {
+            members.remove(index);
+        }
+        env.storage()
+            .persistent()
+            .set(&DataKey::OrgMembers(org_id.clone()), &members);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "member_removed"), org_id),
+            member,
+        );
+    }
+
+    /// Register a new agent owned by an organization
+    ///
+    /// # Arguments
+    /// * `caller` - Admin or maintainer of the organization
+    /// * `org_id` - ID of the owning organization
+    /// * `agent_id` - Unique identifier for the agent
+    /// * `payout` - Address that receives the agent's payments; stored as the agent's
+    ///   `owner`, but only members act for the agent (see `is_agent_operator`)
+    /// * `price` - Price per execution in stroops (1 XLM = 10,000,000 stroops)
+    /// * `metadata_uri` - URI pointing to additional agent metadata (e.g., IPFS)
+    pub fn register_org_agent(
+        env: Env,
+        caller: Address,
+        org_id: String,
+        agent_id: String,
+        payout: Address,
+        price: i128,
+        metadata_uri: String,
+    ) {
+        Self::require_not_paused(&env);
+
+        Self::require_org_role(&env, &caller, &org_id, &[Role::Admin, Role::Maintainer]);
+
+        let agent_info = AgentInfo {
+            agent_id: agent_id.clone(),
+            owner: payout.clone(),
+            price,
+            metadata_uri: metadata_uri.clone(),
+            is_active: true,
+            registered_at: env.ledger().timestamp(),
+            max_open_jobs: 0,
+            org_id: Some(org_id.clone()),
+            unit_price: 0,
+            price_currency: None,
+        };
+        Self::store_new_agent(&env, &agent_info);
+
+        let mut agents = Self::get_agents_by_org(env.clone(), org_id.clone());
+        agents.push_back(agent_id.clone());
+        env.storage()
+            .persistent()
+            .set(&DataKey::AgentsByOrg(org_id), &agents);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "agent_registered"), agent_id),
+            (payout, price, metadata_uri),
+        );
+    }
+
+    /// Change where an organization agent's payments go
+    ///
+    /// # Arguments
+    /// * `caller` - Admin or finance member of the owning organization
+    /// * `agent_id` - ID of the organization agent
+    /// * `payout` - New address that receives the agent's payments
+    pub fn set_payout_address(env: Env, caller: Address, agent_id: String, payout: Address) {
+        Self::require_not_paused(&env);
+
+        let mut agent_info = Self::load_agent(&env, &agent_id);
+        assert!(
+            agent_info.org_id.is_some(),
+            "Agent is not owned by an organization"
+        );
+        Self::require_agent_role(&env, &caller, &agent_info, &[Role::Admin, Role::Finance]);
+
+        let old_payout = agent_info.owner.clone();
+        agent_info.owner = payout.clone();
+        Self::save_agent(&env, &agent_info);
+
+        // Keep the payout address index in step
+        Self::remove_from_owner_agents(&env, &old_payout, agent_id.clone());
+        Self::add_to_owner_agents(&env, &payout, agent_id.clone());
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "payout_updated"), agent_id),
+            (old_payout, payout),
+        );
+    }
+
+    /// Get organization details
+    pub fn get_org(env: Env, org_id: String) -> Organization {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Org(org_id))
+            .expect("Organization not found")
+    }
+
+    /// Get all members of an organization
+    pub fn get_org_members(env: Env, org_id: String) -> Vec<Address> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::OrgMembers(org_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Get a member's role in an organization (`None` if not a member)
+    pub fn get_member_role(env: Env, org_id: String, member: Address) -> Option<Role> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::OrgRole(org_id, member))
+    }
+
+    /// Get all agents owned by an organization
+    pub fn get_agents_by_org(env: Env, org_id: String) -> Vec<String> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::AgentsByOrg(org_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Whether an address acts for an agent, e.g. in escrow disputes
+    ///
+    /// That is the owner of a personal agent, or an admin or maintainer of
+    /// the organization owning an organization agent. An organization
+    /// agent's payout address only receives its payments.
+    ///
+    /// # Arguments
+    /// * `agent_id` - ID of the agent
+    /// * `caller` - Address to check
+    pub fn is_agent_operator(env: Env, agent_id: String, caller: Address) -> bool {
+        let agent_info = Self::load_agent(&env, &agent_id);
+        match agent_info.org_id {
+            Some(org_id) => matches!(
+                Self::get_member_role(env, org_id, caller),
+                Some(Role::Admin | Role::Maintainer)
+            ),
+            None => caller == agent_info.owner,
+        }
+    }
+}
+
+impl AgentRegistryContract {
+    /// Check the caller may manage an agent
+    ///
+    /// Personal agents can only be managed by their owner; organization
+    /// agents by members holding one of `roles`.
+    pub(crate) fn require_agent_role(env: &Env, caller: &Address, agent_info: &AgentInfo, roles: &[Role]) {
+        match &agent_info.org_id {
+            Some(org_id) => Self::require_org_role(env, caller, org_id, roles),
+            None => {
+                caller.require_auth();
+                assert!(*caller == agent_info.owner, "Caller is not the agent owner");
+            }
+        }
+    }
+
+    pub(crate) fn remove_from_org_agents(env: &Env, org_id: &String, agent_id: String) {
+        let mut agents = Self::get_agents_by_org(env.clone(), org_id.clone());
+        if let Some(index) = agents.first_index_of(&agent_id) {
+            agents.remove(index);
+        }
+        env.storage()
+            .persistent()
+            .set(&DataKey::AgentsByOrg(org_id.clone()), &agents);
+    }
+
+    fn require_org_role(env: &Env, caller: &Address, org_id: &String, roles: &[Role]) {
+        caller.require_auth();
+
+        let role = Self::get_member_role(env.clone(), org_id.clone(), caller.clone())
+            .expect("Not a member");
+        assert!(roles.contains(&role), "Insufficient role");
+    }
+
+    fn require_other_admin(env: &Env, org_id: &String, member: &Address) {
+        // An organization must always keep at least one admin
+        let has_other_admin = Self::get_org_members(env.clone(), org_id.clone())
+            .iter()
+            .any(|other| {
+                other != *member
+                    && Self::get_member_role(env.clone(), org_id.clone(), other) == Some(Role::Admin)
+            });
+        assert!(has_other_admin, "Organization needs an admin");
+    }
+
+    fn store_member_role(env: &Env, org_id: &String, member: &Address, role: Role) {
+        if Self::get_member_role(env.clone(), org_id.clone(), member.clone()).is_none() {
+            let mut members = Self::get_org_members(env.clone(), org_id.clone());
+            members.push_back(member.clone());
+            env.storage()
+                .persistent()
+                .set(&DataKey::OrgMembers(org_id.clone()), &members);
+        }
+        env.storage()
+            .persistent()
+            .set(&DataKey::OrgRole(org_id.clone(), member.clone()), &role);
+    }
+}
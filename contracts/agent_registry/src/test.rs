@@ -1,7 +1,11 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::{testutils::Address as _, Env};
+use org::Role;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    vec, Env,
+};
 
 #[test]
 fn test_register_agent() {
@@ -24,7 +28,7 @@ fn test_register_agent() {
     assert_eq!(agent.owner, owner);
     assert_eq!(agent.price, 1000000);
     assert_eq!(agent.metadata_uri, metadata_uri);
-    assert_eq!(agent.is_active, true);
+    assert!(agent.is_active);
 }
 
 #[test]
@@ -63,7 +67,7 @@ fn test_update_agent_price() {
     client.register_agent(&agent_id, &owner, &1000000, &metadata_uri);
 
     // Update price
-    client.update_agent_price(&agent_id, &2000000);
+    client.update_agent_price(&owner, &agent_id, &2000000);
 
     // Verify price was updated
     let agent = client.get_agent(&agent_id);
@@ -87,7 +91,7 @@ fn test_transfer_ownership() {
     client.register_agent(&agent_id, &old_owner, &1000000, &metadata_uri);
 
     // Transfer ownership
-    client.transfer_ownership(&agent_id, &new_owner);
+    client.transfer_ownership(&old_owner, &agent_id, &new_owner);
 
     // Verify ownership was transferred
     let agent = client.get_agent(&agent_id);
@@ -118,14 +122,14 @@ fn test_deactivate_and_activate_agent() {
     client.register_agent(&agent_id, &owner, &1000000, &metadata_uri);
 
     // Deactivate
-    client.deactivate_agent(&agent_id);
+    client.deactivate_agent(&owner, &agent_id);
     let agent = client.get_agent(&agent_id);
-    assert_eq!(agent.is_active, false);
+    assert!(!agent.is_active);
 
     // Reactivate
-    client.activate_agent(&agent_id);
+    client.activate_agent(&owner, &agent_id);
     let agent = client.get_agent(&agent_id);
-    assert_eq!(agent.is_active, true);
+    assert!(agent.is_active);
 }
 
 #[test]
@@ -174,3 +178,366 @@ fn test_get_all_agents() {
     let agents = client.get_all_agents();
     assert_eq!(agents.len(), 2);
 }
+
+#[test]
+fn test_set_max_open_jobs() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentRegistryContract, ());
+    let client = AgentRegistryContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let agent_id = String::from_str(&env, "reddit-scout");
+    let metadata_uri = String::from_str(&env, "ipfs://QmTest123");
+
+    // Register agent - unlimited by default
+    client.register_agent(&agent_id, &owner, &1000000, &metadata_uri);
+    assert_eq!(client.get_agent(&agent_id).max_open_jobs, 0);
+
+    // Limit capacity
+    client.set_max_open_jobs(&owner, &agent_id, &3);
+    assert_eq!(client.get_agent(&agent_id).max_open_jobs, 3);
+}
+
+#[test]
+fn test_set_unit_price() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentRegistryContract, ());
+    let client = AgentRegistryContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let agent_id = String::from_str(&env, "reddit-scout");
+    let metadata_uri = String::from_str(&env, "ipfs://QmTest123");
+
+    // Register agent - no metered pricing by default
+    client.register_agent(&agent_id, &owner, &1000000, &metadata_uri);
+    assert_eq!(client.get_agent(&agent_id).unit_price, 0);
+
+    client.set_unit_price(&owner, &agent_id, &250);
+    assert_eq!(client.get_agent(&agent_id).unit_price, 250);
+    assert!(client.try_set_unit_price(&owner, &agent_id, &-1).is_err());
+}
+
+#[test]
+fn test_set_price_currency() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentRegistryContract, ());
+    let client = AgentRegistryContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let other = Address::generate(&env);
+    let agent_id = String::from_str(&env, "reddit-scout");
+    let metadata_uri = String::from_str(&env, "ipfs://QmTest123");
+
+    // Register agent - priced in stroops by default
+    client.register_agent(&agent_id, &owner, &1000000, &metadata_uri);
+    assert_eq!(client.get_agent(&agent_id).price_currency, None);
+
+    // $2.50 with 7 decimals
+    let usd = Some(Symbol::new(&env, "USD"));
+    client.set_price_currency(&owner, &agent_id, &25000000, &usd);
+    let agent = client.get_agent(&agent_id);
+    assert_eq!(agent.price, 25000000);
+    assert_eq!(agent.price_currency, usd);
+    assert!(client.try_set_price_currency(&other, &agent_id, &1, &None).is_err());
+
+    // Back to a plain stroop price
+    client.set_price_currency(&owner, &agent_id, &1000000, &None);
+    assert_eq!(client.get_agent(&agent_id).price_currency, None);
+}
+
+#[test]
+fn test_set_agent_tags() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentRegistryContract, ());
+    let client = AgentRegistryContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let other = Address::generate(&env);
+    let agent_id = String::from_str(&env, "reddit-scout");
+    let metadata_uri = String::from_str(&env, "ipfs://QmTest123");
+
+    client.register_agent(&agent_id, &owner, &1000000, &metadata_uri);
+    assert_eq!(client.get_agent_tags(&agent_id).len(), 0);
+
+    let tags = vec![&env, Symbol::new(&env, "scraping"), Symbol::new(&env, "reddit")];
+    client.set_agent_tags(&owner, &agent_id, &tags);
+    assert_eq!(client.get_agent_tags(&agent_id), tags);
+    assert!(client.try_set_agent_tags(&other, &agent_id, &Vec::new(&env)).is_err());
+}
+
+#[test]
+fn test_set_verifier() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentRegistryContract, ());
+    let client = AgentRegistryContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let other = Address::generate(&env);
+    let verifier = Address::generate(&env);
+    let agent_id = String::from_str(&env, "code-agent");
+    let metadata_uri = String::from_str(&env, "ipfs://QmTest123");
+
+    client.register_agent(&agent_id, &owner, &1000000, &metadata_uri);
+    assert_eq!(client.get_verifier(&agent_id), None);
+
+    client.set_verifier(&owner, &agent_id, &Some(verifier.clone()));
+    assert_eq!(client.get_verifier(&agent_id), Some(verifier));
+    assert!(client.try_set_verifier(&other, &agent_id, &None).is_err());
+
+    client.set_verifier(&owner, &agent_id, &None);
+    assert_eq!(client.get_verifier(&agent_id), None);
+}
+
+#[test]
+fn test_pause_and_unpause() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentRegistryContract, ());
+    let client = AgentRegistryContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let agent_id1 = String::from_str(&env, "reddit-scout");
+    let agent_id2 = String::from_str(&env, "content-gen");
+    let metadata_uri = String::from_str(&env, "ipfs://QmTest123");
+
+    client.initialize(&admin);
+    client.register_agent(&agent_id1, &owner, &1000000, &metadata_uri);
+
+    // Pause blocks changes but owners can still take agents offline
+    client.pause();
+    assert!(client.is_paused());
+    assert!(client.try_register_agent(&agent_id2, &owner, &2000000, &metadata_uri).is_err());
+    assert!(client.try_update_agent_price(&owner, &agent_id1, &2000000).is_err());
+    client.deactivate_agent(&owner, &agent_id1);
+    assert!(!client.get_agent(&agent_id1).is_active);
+
+    client.unpause();
+    assert!(!client.is_paused());
+    client.register_agent(&agent_id2, &owner, &2000000, &metadata_uri);
+    assert_eq!(client.get_all_agents().len(), 2);
+}
+
+#[test]
+fn test_upgrade_is_timelocked() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentRegistryContract, ());
+    let client = AgentRegistryContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    // ETA must respect the minimum delay
+    let wasm_hash = BytesN::from_array(&env, &[0u8; 32]);
+    assert!(client.try_schedule_upgrade(&wasm_hash, &172_799).is_err());
+    assert!(client.try_execute_upgrade().is_err());
+
+    // Anyone can see the pending upgrade while it waits
+    client.schedule_upgrade(&wasm_hash, &172_800);
+    let scheduled = client.get_scheduled_upgrade().unwrap();
+    assert_eq!(scheduled.wasm_hash, wasm_hash);
+    assert_eq!(scheduled.eta, 172_800);
+    assert!(client.try_execute_upgrade().is_err());
+
+    // A cancelled upgrade can never run
+    client.cancel_upgrade();
+    assert_eq!(client.get_scheduled_upgrade(), None);
+    env.ledger().with_mut(|li| li.timestamp = 172_800);
+    assert!(client.try_execute_upgrade().is_err());
+    assert_eq!(client.version(), Version { code: 2, schema: 2 });
+}
+
+#[test]
+fn test_upgrade_requires_admin() {
+    let env = Env::default();
+
+    let contract_id = env.register(AgentRegistryContract, ());
+    let client = AgentRegistryContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    // Without the admin's signature no upgrade can be queued
+    let wasm_hash = BytesN::from_array(&env, &[0u8; 32]);
+    assert!(client.try_schedule_upgrade(&wasm_hash, &172_800).is_err());
+    assert_eq!(client.get_scheduled_upgrade(), None);
+}
+
+#[test]
+fn test_legacy_agent_record_is_migrated() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentRegistryContract, ());
+    let client = AgentRegistryContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let agent_id = String::from_str(&env, "reddit-scout");
+
+    // Write a record in the layout used before storage versioning
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(
+            &DataKey::Agent(agent_id.clone()),
+            &AgentInfoV1 {
+                agent_id: agent_id.clone(),
+                owner: owner.clone(),
+                price: 1000000,
+                metadata_uri: String::from_str(&env, "ipfs://QmTest123"),
+                is_active: true,
+                registered_at: 0,
+            },
+        );
+    });
+
+    let agent = client.get_agent(&agent_id);
+    assert_eq!(agent.owner, owner);
+    assert_eq!(agent.price, 1000000);
+    assert_eq!(agent.max_open_jobs, 0);
+    assert_eq!(agent.org_id, None);
+    assert_eq!(agent.unit_price, 0);
+    assert_eq!(agent.price_currency, None);
+
+    // The record is rewritten in the newest layout
+    let stored: AgentRecord = env.as_contract(&contract_id, || {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Agent(agent_id.clone()))
+            .unwrap()
+    });
+    assert_eq!(stored, AgentRecord::V2(agent));
+
+    // Updates keep working on migrated records
+    client.update_agent_price(&owner, &agent_id, &2000000);
+    assert_eq!(client.get_agent(&agent_id).price, 2000000);
+
+    assert_eq!(client.version(), Version { code: 2, schema: 2 });
+}
+
+#[test]
+fn test_org_roles_gate_agent_management() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentRegistryContract, ());
+    let client = AgentRegistryContractClient::new(&env, &contract_id);
+
+    let org_admin = Address::generate(&env);
+    let maintainer = Address::generate(&env);
+    let finance = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    let payout = Address::generate(&env);
+    let new_payout = Address::generate(&env);
+    let org_id = String::from_str(&env, "acme");
+    let agent_id = String::from_str(&env, "reddit-scout");
+    let metadata_uri = String::from_str(&env, "ipfs://QmTest123");
+
+    client.create_org(&org_admin, &org_id);
+    client.set_member_role(&org_admin, &org_id, &maintainer, &Role::Maintainer);
+    client.set_member_role(&org_admin, &org_id, &finance, &Role::Finance);
+    assert_eq!(client.get_org_members(&org_id).len(), 3);
+    assert_eq!(client.get_member_role(&org_id, &finance), Some(Role::Finance));
+
+    // Finance cannot list agents; maintainers can
+    assert!(client
+        .try_register_org_agent(&finance, &org_id, &agent_id, &payout, &1000000, &metadata_uri)
+        .is_err());
+    client.register_org_agent(&maintainer, &org_id, &agent_id, &payout, &1000000, &metadata_uri);
+    let agent = client.get_agent(&agent_id);
+    assert_eq!(agent.owner, payout);
+    assert_eq!(agent.org_id, Some(org_id.clone()));
+    assert_eq!(client.get_agents_by_org(&org_id).len(), 1);
+
+    // Admins and maintainers act for the agent; the payout address does not
+    assert!(client.is_agent_operator(&agent_id, &org_admin));
+    assert!(client.is_agent_operator(&agent_id, &maintainer));
+    assert!(!client.is_agent_operator(&agent_id, &finance));
+    assert!(!client.is_agent_operator(&agent_id, &payout));
+
+    // Listing changes need admin or maintainer
+    client.update_agent_price(&maintainer, &agent_id, &2000000);
+    assert!(client.try_update_agent_price(&finance, &agent_id, &3000000).is_err());
+    assert!(client.try_update_agent_price(&outsider, &agent_id, &3000000).is_err());
+    assert!(client.try_update_agent_price(&payout, &agent_id, &3000000).is_err());
+    client.deactivate_agent(&maintainer, &agent_id);
+    assert!(!client.get_agent(&agent_id).is_active);
+
+    // Payout changes need admin or finance
+    assert!(client.try_set_payout_address(&maintainer, &agent_id, &new_payout).is_err());
+    client.set_payout_address(&finance, &agent_id, &new_payout);
+    assert_eq!(client.get_agent(&agent_id).owner, new_payout);
+    assert_eq!(client.get_agents_by_owner(&payout).len(), 0);
+    assert_eq!(client.get_agents_by_owner(&new_payout).len(), 1);
+
+    // Only org admins can transfer the agent out of the organization
+    assert!(client.try_transfer_ownership(&maintainer, &agent_id, &outsider).is_err());
+    client.transfer_ownership(&org_admin, &agent_id, &outsider);
+    let agent = client.get_agent(&agent_id);
+    assert_eq!(agent.owner, outsider);
+    assert_eq!(agent.org_id, None);
+    assert_eq!(client.get_agents_by_org(&org_id).len(), 0);
+    assert!(client.is_agent_operator(&agent_id, &outsider));
+    assert!(!client.is_agent_operator(&agent_id, &org_admin));
+
+    // Removed members lose access
+    client.remove_member(&org_admin, &org_id, &maintainer);
+    assert_eq!(client.get_member_role(&org_id, &maintainer), None);
+}
+
+#[test]
+#[should_panic(expected = "Organization needs an admin")]
+fn test_cannot_remove_last_org_admin() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentRegistryContract, ());
+    let client = AgentRegistryContractClient::new(&env, &contract_id);
+
+    let org_admin = Address::generate(&env);
+    let maintainer = Address::generate(&env);
+    let org_id = String::from_str(&env, "acme");
+
+    client.create_org(&org_admin, &org_id);
+    client.set_member_role(&org_admin, &org_id, &maintainer, &Role::Maintainer);
+
+    // Demoting the only admin would leave the organization unmanageable
+    client.set_member_role(&org_admin, &org_id, &org_admin, &Role::Maintainer);
+}
+
+#[test]
+fn test_quote_key_is_cleared_on_transfer() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(AgentRegistryContract, ());
+    let client = AgentRegistryContractClient::new(&env, &contract_id);
+
+    let owner = Address::generate(&env);
+    let new_owner = Address::generate(&env);
+    let agent_id = String::from_str(&env, "reddit-scout");
+    let metadata_uri = String::from_str(&env, "ipfs://QmTest123");
+    let quote_key = BytesN::from_array(&env, &[3u8; 32]);
+
+    client.register_agent(&agent_id, &owner, &1000000, &metadata_uri);
+    assert_eq!(client.get_quote_key(&agent_id), None);
+
+    assert!(client.try_set_quote_key(&new_owner, &agent_id, &quote_key).is_err());
+    client.set_quote_key(&owner, &agent_id, &quote_key);
+    assert_eq!(client.get_quote_key(&agent_id), Some(quote_key));
+
+    // The new owner must register their own key
+    client.transfer_ownership(&owner, &agent_id, &new_owner);
+    assert_eq!(client.get_quote_key(&agent_id), None);
+}
@@ -0,0 +1,92 @@
+use soroban_sdk::{contractimpl, contracttype, BytesN, Env, Symbol};
+
+use crate::{AgentRegistryContract, AgentRegistryContractArgs, AgentRegistryContractClient, DataKey};
+
+/// Minimum delay between scheduling and running an upgrade (48 hours)
+const MIN_DELAY: u64 = 172_800;
+
+/// Time after its ETA during which a scheduled upgrade can still run (14 days)
+const GRACE_PERIOD: u64 = 1_209_600;
+
+/// Code upgrade waiting out the timelock
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScheduledUpgrade {
+    pub wasm_hash: BytesN<32>,
+    pub eta: u64,
+    pub scheduled_at: u64,
+}
+
+#[contractimpl]
+impl AgentRegistryContract {
+    /// Queue a replacement of the contract code
+    ///
+    /// Only one upgrade can be queued at a time; scheduling again replaces it
+    /// and restarts the delay.
+    ///
+    /// # Arguments
+    /// * `wasm_hash` - Hash of the uploaded wasm to switch to
+    /// * `eta` - Earliest time it can run; at least the minimum delay from now
+    pub fn schedule_upgrade(env: Env, wasm_hash: BytesN<32>, eta: u64) {
+        Self::get_admin(env.clone()).require_auth();
+
+        let now = env.ledger().timestamp();
+        assert!(eta >= now + MIN_DELAY, "ETA is before the minimum delay");
+
+        let scheduled = ScheduledUpgrade {
+            wasm_hash: wasm_hash.clone(),
+            eta,
+            scheduled_at: now,
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::ScheduledUpgrade, &scheduled);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "upgrade_scheduled"),),
+            (wasm_hash, eta),
+        );
+    }
+
+    /// Replace the contract code once the queued upgrade's ETA has passed
+    ///
+    /// Agent records written by older schema versions are upgraded when next read.
+    pub fn execute_upgrade(env: Env) {
+        Self::get_admin(env.clone()).require_auth();
+
+        let scheduled = Self::get_scheduled_upgrade(env.clone()).expect("No upgrade scheduled");
+        let now = env.ledger().timestamp();
+        assert!(now >= scheduled.eta, "Upgrade is still timelocked");
+        assert!(now <= scheduled.eta + GRACE_PERIOD, "Upgrade has expired");
+
+        env.storage().persistent().remove(&DataKey::ScheduledUpgrade);
+
+        // Emit event before the upgrade swaps out the running code
+        env.events().publish(
+            (Symbol::new(&env, "contract_upgraded"),),
+            scheduled.wasm_hash.clone(),
+        );
+
+        env.deployer().update_current_contract_wasm(scheduled.wasm_hash);
+    }
+
+    /// Drop the queued upgrade before it runs
+    pub fn cancel_upgrade(env: Env) {
+        Self::get_admin(env.clone()).require_auth();
+
+        let scheduled = Self::get_scheduled_upgrade(env.clone()).expect("No upgrade scheduled");
+        env.storage().persistent().remove(&DataKey::ScheduledUpgrade);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "upgrade_cancelled"),),
+            scheduled.wasm_hash,
+        );
+    }
+
+    /// Get the queued upgrade, if any
+    pub fn get_scheduled_upgrade(env: Env) -> Option<ScheduledUpgrade> {
+        env.storage().persistent().get(&DataKey::ScheduledUpgrade)
+    }
+}
@@ -0,0 +1,13 @@
+use soroban_sdk::{contractclient, Address, Env};
+
+/// Subset of the agent registry interface used by the factory
+#[contractclient(name = "RegistryClient")]
+pub trait AgentRegistryInterface {
+    fn initialize(env: Env, admin: Address);
+}
+
+/// Subset of the agent escrow interface used by the factory
+#[contractclient(name = "EscrowClient")]
+pub trait AgentEscrowInterface {
+    fn initialize(env: Env, admin: Address, registry: Address, arbiter: Address, fee_bps: u32);
+}
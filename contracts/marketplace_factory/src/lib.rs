@@ -0,0 +1,236 @@
+#![no_std]
+
+use soroban_sdk::{contract, contractimpl, contracttype, Address, BytesN, Env, Symbol, Vec};
+
+pub mod interfaces;
+
+use interfaces::{EscrowClient, RegistryClient};
+
+/// Settings a partner's marketplace starts with
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MarketplaceConfig {
+    /// Platform fee the escrow takes from agent payouts, in basis points
+    pub fee_bps: u32,
+    /// Address that resolves the escrow's disputes
+    pub arbiter: Address,
+}
+
+/// Marketplace instance deployed by the factory
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Marketplace {
+    pub id: u64,
+    pub admin: Address,
+    pub registry: Address,
+    pub escrow: Address,
+    pub config: MarketplaceConfig,
+    pub created_at: u64,
+}
+
+/// Storage keys
+#[contracttype]
+pub enum DataKey {
+    Admin,
+    RegistryWasm,
+    EscrowWasm,
+    MarketplaceCounter,
+    Marketplace(u64),
+    MarketplacesByAdmin(Address),
+}
+
+#[contract]
+pub struct MarketplaceFactoryContract;
+
+#[contractimpl]
+impl MarketplaceFactoryContract {
+    /// Initialize the factory
+    ///
+    /// # Arguments
+    /// * `admin` - Address allowed to deploy marketplaces and change the code they run
+    /// * `registry_wasm_hash` - Hash of the uploaded agent registry wasm
+    /// * `escrow_wasm_hash` - Hash of the uploaded agent escrow wasm
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        registry_wasm_hash: BytesN<32>,
+        escrow_wasm_hash: BytesN<32>,
+    ) {
+        assert!(
+            !env.storage().persistent().has(&DataKey::Admin),
+            "Already initialized"
+        );
+
+        env.storage().persistent().set(&DataKey::Admin, &admin);
+        env.storage()
+            .persistent()
+            .set(&DataKey::RegistryWasm, &registry_wasm_hash);
+        env.storage()
+            .persistent()
+            .set(&DataKey::EscrowWasm, &escrow_wasm_hash);
+        env.storage()
+            .persistent()
+            .set(&DataKey::MarketplaceCounter, &0u64);
+    }
+
+    /// Change the code used for new marketplaces
+    ///
+    /// Marketplaces that are already deployed keep their code; their own
+    /// admins upgrade them through the escrow timelock.
+    ///
+    /// # Arguments
+    /// * `registry_wasm_hash` - Hash of the uploaded agent registry wasm
+    /// * `escrow_wasm_hash` - Hash of the uploaded agent escrow wasm
+    pub fn set_wasm_hashes(env: Env, registry_wasm_hash: BytesN<32>, escrow_wasm_hash: BytesN<32>) {
+        Self::get_admin(env.clone()).require_auth();
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::RegistryWasm, &registry_wasm_hash);
+        env.storage()
+            .persistent()
+            .set(&DataKey::EscrowWasm, &escrow_wasm_hash);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "wasm_updated"),),
+            (registry_wasm_hash, escrow_wasm_hash),
+        );
+    }
+
+    /// Deploy and initialize a paired registry and escrow for a partner
+    ///
+    /// # Arguments
+    /// * `partner_admin` - Admin of the new marketplace
+    /// * `config` - Fee and arbiter for the new escrow; the partner admin can
+    ///   change both later through the escrow timelock
+    pub fn deploy_marketplace(env: Env, partner_admin: Address, config: MarketplaceConfig) -> u64 {
+        Self::get_admin(env.clone()).require_auth();
+
+        let marketplace_id: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::MarketplaceCounter)
+            .unwrap_or(0)
+            + 1;
+
+        // Deploy both contracts at addresses derived from the marketplace ID
+        let registry = env
+            .deployer()
+            .with_current_contract(Self::salt(&env, marketplace_id, 0))
+            .deploy_v2(Self::get_registry_wasm(env.clone()), ());
+        let escrow = env
+            .deployer()
+            .with_current_contract(Self::salt(&env, marketplace_id, 1))
+            .deploy_v2(Self::get_escrow_wasm(env.clone()), ());
+
+        RegistryClient::new(&env, &registry).initialize(&partner_admin);
+        EscrowClient::new(&env, &escrow).initialize(
+            &partner_admin,
+            &registry,
+            &config.arbiter,
+            &config.fee_bps,
+        );
+
+        // Record the instance in the directory
+        let marketplace = Marketplace {
+            id: marketplace_id,
+            admin: partner_admin.clone(),
+            registry: registry.clone(),
+            escrow: escrow.clone(),
+            config,
+            created_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::Marketplace(marketplace_id), &marketplace);
+        env.storage()
+            .persistent()
+            .set(&DataKey::MarketplaceCounter, &marketplace_id);
+        Self::add_to_admin_marketplaces(&env, &partner_admin, marketplace_id);
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "marketplace_deployed"), marketplace_id),
+            (partner_admin, registry, escrow),
+        );
+
+        marketplace_id
+    }
+
+    /// Get the factory admin
+    pub fn get_admin(env: Env) -> Address {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Admin)
+            .expect("Not initialized")
+    }
+
+    /// Get the registry wasm hash used for new marketplaces
+    pub fn get_registry_wasm(env: Env) -> BytesN<32> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::RegistryWasm)
+            .expect("Not initialized")
+    }
+
+    /// Get the escrow wasm hash used for new marketplaces
+    pub fn get_escrow_wasm(env: Env) -> BytesN<32> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::EscrowWasm)
+            .expect("Not initialized")
+    }
+
+    /// Get a deployed marketplace
+    ///
+    /// # Arguments
+    /// * `marketplace_id` - ID returned by `deploy_marketplace`
+    pub fn get_marketplace(env: Env, marketplace_id: u64) -> Marketplace {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Marketplace(marketplace_id))
+            .expect("Marketplace not found")
+    }
+
+    /// Get the number of marketplaces deployed so far (IDs run from 1 to this value)
+    pub fn get_marketplace_count(env: Env) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::MarketplaceCounter)
+            .unwrap_or(0)
+    }
+
+    /// Get all marketplaces administered by an address
+    pub fn get_marketplaces_by_admin(env: Env, admin: Address) -> Vec<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::MarketplacesByAdmin(admin))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    // Internal helper functions
+
+    fn salt(env: &Env, marketplace_id: u64, index: u8) -> BytesN<32> {
+        let mut salt = [0u8; 32];
+        salt[..8].copy_from_slice(&marketplace_id.to_be_bytes());
+        salt[8] = index;
+        BytesN::from_array(env, &salt)
+    }
+
+    fn add_to_admin_marketplaces(env: &Env, admin: &Address, marketplace_id: u64) {
+        let mut marketplaces: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::MarketplacesByAdmin(admin.clone()))
+            .unwrap_or(Vec::new(env));
+
+        marketplaces.push_back(marketplace_id);
+        env.storage()
+            .persistent()
+            .set(&DataKey::MarketplacesByAdmin(admin.clone()), &marketplaces);
+    }
+}
+
+#[cfg(test)]
+mod test;
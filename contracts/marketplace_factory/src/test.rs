@@ -0,0 +1,110 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{testutils::Address as _, Env, String};
+
+// Build the contracts first with
+// `cargo build --release --target wasm32v1-none -p agent_registry -p agent_escrow`
+mod agent_registry {
+    soroban_sdk::contractimport!(file = "../../target/wasm32v1-none/release/agent_registry.wasm");
+}
+
+#[allow(clippy::too_many_arguments)]
+mod agent_escrow {
+    soroban_sdk::contractimport!(file = "../../target/wasm32v1-none/release/agent_escrow.wasm");
+}
+
+/// Upload a contract wasm, as the deploy scripts do before initializing the factory
+fn upload_wasm(env: &Env, wasm: &[u8]) -> BytesN<32> {
+    // The deploy scripts upload the wasm after `stellar contract optimize`; the
+    // unoptimized build can cost more than one transaction's budget to upload
+    env.cost_estimate().budget().reset_unlimited();
+    let hash = env.deployer().upload_contract_wasm(wasm);
+    env.cost_estimate().budget().reset_default();
+    hash
+}
+
+fn create_factory<'a>(env: &Env, admin: &Address) -> MarketplaceFactoryContractClient<'a> {
+    let client = MarketplaceFactoryContractClient::new(
+        env,
+        &env.register(MarketplaceFactoryContract, ()),
+    );
+    client.initialize(
+        admin,
+        &upload_wasm(env, agent_registry::WASM),
+        &upload_wasm(env, agent_escrow::WASM),
+    );
+    client
+}
+
+fn config(fee_bps: u32, arbiter: &Address) -> MarketplaceConfig {
+    MarketplaceConfig {
+        fee_bps,
+        arbiter: arbiter.clone(),
+    }
+}
+
+#[test]
+fn test_deploy_marketplace() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let partner = Address::generate(&env);
+    let arbiter = Address::generate(&env);
+    let factory = create_factory(&env, &admin);
+
+    let marketplace_id = factory.deploy_marketplace(&partner, &config(250, &arbiter));
+    assert_eq!(marketplace_id, 1);
+
+    // The directory records the new pair
+    let marketplace = factory.get_marketplace(&marketplace_id);
+    assert_eq!(marketplace.admin, partner);
+    assert_eq!(marketplace.config, config(250, &arbiter));
+    assert_eq!(factory.get_marketplace_count(), 1);
+    assert_eq!(factory.get_marketplaces_by_admin(&partner).len(), 1);
+
+    // Both contracts are initialized for the partner
+    let registry = agent_registry::Client::new(&env, &marketplace.registry);
+    let escrow = agent_escrow::Client::new(&env, &marketplace.escrow);
+    assert_eq!(registry.get_admin(), partner);
+    assert_eq!(escrow.get_admin(), partner);
+    assert_eq!(escrow.get_arbiter(), arbiter);
+    assert_eq!(escrow.get_fee_bps(), 250);
+
+    // The registry is usable straight away
+    let owner = Address::generate(&env);
+    let agent_id = String::from_str(&env, "test-agent");
+    registry.register_agent(&agent_id, &owner, &100, &String::from_str(&env, "ipfs://QmTest123"));
+    assert_eq!(registry.get_agent(&agent_id).owner, owner);
+}
+
+#[test]
+fn test_marketplaces_are_independent() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let partner = Address::generate(&env);
+    let other_partner = Address::generate(&env);
+    let factory = create_factory(&env, &admin);
+
+    let first = factory.get_marketplace(&factory.deploy_marketplace(&partner, &config(100, &partner)));
+    let second = factory.get_marketplace(&factory.deploy_marketplace(&other_partner, &config(0, &admin)));
+    let third = factory.get_marketplace(&factory.deploy_marketplace(&partner, &config(500, &partner)));
+
+    assert_ne!(first.registry, second.registry);
+    assert_ne!(first.escrow, second.escrow);
+    let escrow = agent_escrow::Client::new(&env, &second.escrow);
+    assert_eq!(escrow.get_fee_bps(), 0);
+    assert_eq!(escrow.get_arbiter(), admin);
+    assert_eq!(factory.get_marketplace_count(), 3);
+    assert_eq!(
+        factory.get_marketplaces_by_admin(&partner),
+        Vec::from_array(&env, [first.id, third.id])
+    );
+
+    // The escrow rejects fees above its cap, so nothing is recorded
+    assert!(factory.try_deploy_marketplace(&partner, &config(5_000, &partner)).is_err());
+    assert_eq!(factory.get_marketplace_count(), 3);
+}
@@ -4,6 +4,7 @@ import {
   nativeToScVal,
   scValToNative,
   TransactionBuilder,
+  xdr,
 } from "@stellar/stellar-sdk";
 import { rpc } from "@stellar/stellar-sdk";
 import {
@@ -23,10 +24,20 @@ export interface Job {
   agent_owner: string;
   agent_id: string;
   amount: bigint;
-  status: number; // 0: Pending, 1: Completed, 2: Cancelled, 3: Disputed
+  token: string;
+  status: number; // 0: Pending, 1: Completed, 2: Cancelled, 3: Disputed, 4: Released, 5: Resolved, 6: Locked, 7: Funding
   created_at: bigint;
   completed_at: bigint | null;
+  release_at: bigint | null;
   results_hash: Uint8Array | null;
+  // ["Fixed"], ["Metered", { unit_price, units }], ["Streamed", { start, end, withdrawn }]
+  // ["Staged", { pipeline_id, index }] or ["Pooled", { deadline, raised }]
+  kind: [string, ...any[]];
+}
+
+export interface SupportedToken {
+  token: string;
+  min_amount: bigint;
 }
 
 export enum JobStatus {
@@ -34,6 +45,10 @@ export enum JobStatus {
   Completed = 1,
   Cancelled = 2,
   Disputed = 3,
+  Released = 4,
+  Resolved = 5,
+  Locked = 6,
+  Funding = 7,
 }
 
 /**
@@ -53,28 +68,43 @@ export class EscrowContract {
   /**
    * Create a new escrow job
    * @param hirer - Address of the person hiring the agent
-   * @param agentOwner - Address of the agent owner
-   * @param agentId - Agent identifier
+   * @param agentId - Agent identifier; payment goes to its owner in the registry
    * @param amount - Payment amount in stroops
    * @param tokenAddress - Token contract addressfor payment (native token for XLM)
+   * @param clientRef - Optional 32-byte idempotency key (e.g. hash of the MongoDB job id);
+   *   retries with the same key return the existing job instead of funding a new one
+   * @param inputsHash - Optional 32-byte hash of the inputs given to the agent; an agent's
+   *   verifier contract checks its results against it
+   * @param expectedVerifier - Optional verifier contract the hirer accepts; must be the
+   *   agent's current one in the registry, and without it the job has no verifier
    * @returns Job ID
    */
   async createJob(
     hirer: string,
-    agentOwner: string,
     agentId: string,
     amount: number,
     tokenAddress: string,
+    clientRef?: Uint8Array,
+    inputsHash?: Uint8Array,
+    expectedVerifier?: string,
   ): Promise<number> {
     try {
       // Build the transaction
       const operation = this.contract.call(
         "create_job",
         new Address(hirer).toScVal(),
-        new Address(agentOwner).toScVal(),
         nativeToScVal(agentId, { type: "string" }),
         nativeToScVal(amount, { type: "i128" }),
         new Address(tokenAddress).toScVal(),
+        clientRef
+          ? nativeToScVal(clientRef, { type: "bytes" })
+          : xdr.ScVal.scvVoid(),
+        inputsHash
+          ? nativeToScVal(inputsHash, { type: "bytes" })
+          : xdr.ScVal.scvVoid(),
+        expectedVerifier
+          ? new Address(expectedVerifier).toScVal()
+          : xdr.ScVal.scvVoid(),
       );
 
       const account = await stellarServer.getAccount(hirer);
@@ -120,7 +150,8 @@ export class EscrowContract {
   }
 
   /**
-   * Complete a job and release payment
+   * Complete a job; payment is held for the challenge window and credited
+   * to the agent owner's claimable balance by `finalize` afterwards
    * @param jobId - ID of the job to complete
    * @param resultsHash - Hash of the execution results (32 bytes)
    * @param tokenAddress - Token contract address
@@ -171,6 +202,50 @@ export class EscrowContract {
     }
   }
 
+  /**
+   * Release a completed job's held payment once its challenge window has passed
+   * (anyone may call this)
+   * @param jobId - ID of the completed job
+   * @param signerAddress - Address signing the transaction
+   */
+  async finalize(jobId: number, signerAddress: string): Promise<void> {
+    try {
+      const operation = this.contract.call(
+        "finalize",
+        nativeToScVal(jobId, { type: "u64" }),
+      );
+
+      const builtTransaction = await buildTransaction(signerAddress, [
+        operation,
+      ]);
+
+      // Simulate and prepare
+      const simulated =
+        await stellarServer.simulateTransaction(builtTransaction);
+      if (rpc.Api.isSimulationError(simulated)) {
+        throw new Error(`Simulation failed: ${simulated.error}`);
+      }
+
+      const preparedTx = rpc
+        .assembleTransaction(builtTransaction, simulated)
+        .build();
+
+      // Sign and submit
+      const signedXdr = await signTransaction(
+        preparedTx.toXDR(),
+        networkPassphrase,
+      );
+      const signedTx = TransactionBuilder.fromXDR(signedXdr, networkPassphrase);
+      const result = await submitTransaction(signedTx as any);
+
+      // Wait for confirmation
+      await waitForTransaction(result.hash, 180, signerAddress);
+    } catch (error: any) {
+      console.error("Error finalizing job:", error);
+      throw new Error(error.message || "Failed to finalize job");
+    }
+  }
+
   /**
    * Cancel a job and refund the hirer
    * @param jobId - ID of the job to cancel
@@ -220,6 +295,208 @@ export class EscrowContract {
     }
   }
 
+  /**
+   * Withdraw funds credited to an address (agent earnings or dispute refunds)
+   * @param owner - Address whose claimable balance is withdrawn (signs the transaction)
+   * @param tokenAddress - Token contract address
+   * @param amount - Amount to withdraw in stroops
+   */
+  async withdraw(
+    owner: string,
+    tokenAddress: string,
+    amount: number,
+  ): Promise<void> {
+    try {
+      const operation = this.contract.call(
+        "withdraw",
+        new Address(owner).toScVal(),
+        new Address(tokenAddress).toScVal(),
+        nativeToScVal(amount, { type: "i128" }),
+      );
+
+      const builtTransaction = await buildTransaction(owner, [operation]);
+
+      // Simulate and prepare
+      const simulated =
+        await stellarServer.simulateTransaction(builtTransaction);
+      if (rpc.Api.isSimulationError(simulated)) {
+        throw new Error(`Simulation failed: ${simulated.error}`);
+      }
+
+      const preparedTx = rpc
+        .assembleTransaction(builtTransaction, simulated)
+        .build();
+
+      // Sign and submit
+      const signedXdr = await signTransaction(
+        preparedTx.toXDR(),
+        networkPassphrase,
+      );
+      const signedTx = TransactionBuilder.fromXDR(signedXdr, networkPassphrase);
+      const result = await submitTransaction(signedTx as any);
+
+      // Wait for confirmation
+      await waitForTransaction(result.hash, 180, owner);
+    } catch (error: any) {
+      console.error("Error withdrawing:", error);
+      throw new Error(error.message || "Failed to withdraw");
+    }
+  }
+
+  /**
+   * Allow the escrow to pull a token from the hirer for spender-created jobs
+   * (one wallet signature instead of one per job)
+   * @param hirer - Address of the hirer
+   * @param tokenAddress - Token contract address
+   * @param amount - Maximum total the escrow may pull
+   * @param expirationLedger - Ledger after which the approval lapses
+   */
+  async approveEscrowOnToken(
+    hirer: string,
+    tokenAddress: string,
+    amount: number,
+    expirationLedger: number,
+  ): Promise<void> {
+    try {
+      const operation = new Contract(tokenAddress).call(
+        "approve",
+        new Address(hirer).toScVal(),
+        this.contract.address().toScVal(),
+        nativeToScVal(amount, { type: "i128" }),
+        nativeToScVal(expirationLedger, { type: "u32" }),
+      );
+
+      const builtTransaction = await buildTransaction(hirer, [operation]);
+
+      // Simulate and prepare
+      const simulated =
+        await stellarServer.simulateTransaction(builtTransaction);
+      if (rpc.Api.isSimulationError(simulated)) {
+        throw new Error(`Simulation failed: ${simulated.error}`);
+      }
+
+      const preparedTx = rpc
+        .assembleTransaction(builtTransaction, simulated)
+        .build();
+
+      // Sign and submit
+      const signedXdr = await signTransaction(
+        preparedTx.toXDR(),
+        networkPassphrase,
+      );
+      const signedTx = TransactionBuilder.fromXDR(signedXdr, networkPassphrase);
+      const result = await submitTransaction(signedTx as any);
+
+      // Wait for confirmation
+      await waitForTransaction(result.hash, 180, hirer);
+    } catch (error: any) {
+      console.error("Error approving token:", error);
+      throw new Error(error.message || "Failed to approve token");
+    }
+  }
+
+  /**
+   * Let a spender (e.g. the execution backend) open jobs for the hirer
+   * @param hirer - Address of the hirer granting the budget
+   * @param spender - Address allowed to call create_job_for
+   * @param tokenAddress - Token contract address
+   * @param budget - Maximum total job amount per period
+   * @param periodSeconds - Length of a budget period in seconds
+   */
+  async approveSpender(
+    hirer: string,
+    spender: string,
+    tokenAddress: string,
+    budget: number,
+    periodSeconds: number,
+  ): Promise<void> {
+    try {
+      const operation = this.contract.call(
+        "approve_spender",
+        new Address(hirer).toScVal(),
+        new Address(spender).toScVal(),
+        new Address(tokenAddress).toScVal(),
+        nativeToScVal(budget, { type: "i128" }),
+        nativeToScVal(periodSeconds, { type: "u64" }),
+      );
+
+      const builtTransaction = await buildTransaction(hirer, [operation]);
+
+      // Simulate and prepare
+      const simulated =
+        await stellarServer.simulateTransaction(builtTransaction);
+      if (rpc.Api.isSimulationError(simulated)) {
+        throw new Error(`Simulation failed: ${simulated.error}`);
+      }
+
+      const preparedTx = rpc
+        .assembleTransaction(builtTransaction, simulated)
+        .build();
+
+      // Sign and submit
+      const signedXdr = await signTransaction(
+        preparedTx.toXDR(),
+        networkPassphrase,
+      );
+      const signedTx = TransactionBuilder.fromXDR(signedXdr, networkPassphrase);
+      const result = await submitTransaction(signedTx as any);
+
+      // Wait for confirmation
+      await waitForTransaction(result.hash, 180, hirer);
+    } catch (error: any) {
+      console.error("Error approving spender:", error);
+      throw new Error(error.message || "Failed to approve spender");
+    }
+  }
+
+  /**
+   * Revoke a spender's budget
+   * @param hirer - Address of the hirer that granted the budget
+   * @param spender - Address losing access
+   * @param tokenAddress - Token contract address
+   */
+  async revokeSpender(
+    hirer: string,
+    spender: string,
+    tokenAddress: string,
+  ): Promise<void> {
+    try {
+      const operation = this.contract.call(
+        "revoke_spender",
+        new Address(hirer).toScVal(),
+        new Address(spender).toScVal(),
+        new Address(tokenAddress).toScVal(),
+      );
+
+      const builtTransaction = await buildTransaction(hirer, [operation]);
+
+      // Simulate and prepare
+      const simulated =
+        await stellarServer.simulateTransaction(builtTransaction);
+      if (rpc.Api.isSimulationError(simulated)) {
+        throw new Error(`Simulation failed: ${simulated.error}`);
+      }
+
+      const preparedTx = rpc
+        .assembleTransaction(builtTransaction, simulated)
+        .build();
+
+      // Sign and submit
+      const signedXdr = await signTransaction(
+        preparedTx.toXDR(),
+        networkPassphrase,
+      );
+      const signedTx = TransactionBuilder.fromXDR(signedXdr, networkPassphrase);
+      const result = await submitTransaction(signedTx as any);
+
+      // Wait for confirmation
+      await waitForTransaction(result.hash, 180, hirer);
+    } catch (error: any) {
+      console.error("Error revoking spender:", error);
+      throw new Error(error.message || "Failed to revoke spender");
+    }
+  }
+
   /**
    * Get job details from the contract
    * @param jobId - ID of the job to retrieve
@@ -260,6 +537,76 @@ export class EscrowContract {
     }
   }
 
+  /**
+   * Get the balance an address can withdraw for a token
+   * @param owner - Address holding the balance
+   * @param tokenAddress - Token contract address
+   * @returns Claimable amount in stroops
+   */
+  async getClaimable(owner: string, tokenAddress: string): Promise<bigint> {
+    try {
+      const operation = this.contract.call(
+        "get_claimable",
+        new Address(owner).toScVal(),
+        new Address(tokenAddress).toScVal(),
+      );
+
+      const dummyAccount =
+        "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF";
+      const builtTransaction = await buildTransaction(dummyAccount, [
+        operation,
+      ]);
+
+      const simulated =
+        await stellarServer.simulateTransaction(builtTransaction);
+
+      if (rpc.Api.isSimulationError(simulated)) {
+        throw new Error(`Simulation failed: ${simulated.error}`);
+      }
+
+      if (!simulated.result) {
+        return BigInt(0);
+      }
+
+      return BigInt(scValToNative(simulated.result.retval));
+    } catch (error: any) {
+      console.error("Error getting claimable balance:", error);
+      throw new Error(error.message || "Failed to get claimable balance");
+    }
+  }
+
+  /**
+   * Get the tokens the escrow accepts for new jobs
+   * @returns Token contract addresses with their minimum job amounts
+   */
+  async getSupportedTokens(): Promise<SupportedToken[]> {
+    try {
+      const operation = this.contract.call("get_supported_tokens");
+
+      const dummyAccount =
+        "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF";
+      const builtTransaction = await buildTransaction(dummyAccount, [
+        operation,
+      ]);
+
+      const simulated =
+        await stellarServer.simulateTransaction(builtTransaction);
+
+      if (rpc.Api.isSimulationError(simulated)) {
+        throw new Error(`Simulation failed: ${simulated.error}`);
+      }
+
+      if (!simulated.result) {
+        return [];
+      }
+
+      return scValToNative(simulated.result.retval) as SupportedToken[];
+    } catch (error: any) {
+      console.error("Error getting supported tokens:", error);
+      return [];
+    }
+  }
+
   /**
    * Get all jobs for a hirer
    * @param hirerAddress - Address of the hirer
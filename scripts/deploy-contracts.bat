@@ -49,8 +49,22 @@ stellar contract optimize --wasm target\wasm32v1-none\release\agent_registry.was
 echo [OK] Contracts optimized
 echo.
 
+REM Deploy registry contract
+echo Step 3: Deploying agent_registry contract...
+for /f "tokens=*" %%i in ('stellar contract deploy --wasm target\wasm32v1-none\release\agent_registry.wasm --source %WALLET% --network %NETWORK%') do set REGISTRY_CONTRACT_ID=%%i
+
+echo [OK] Registry contract deployed
+echo    Contract ID: %REGISTRY_CONTRACT_ID%
+echo.
+
+for /f "tokens=*" %%i in ('stellar keys address %WALLET%') do set ADMIN_ADDRESS=%%i
+stellar contract invoke --id %REGISTRY_CONTRACT_ID% --source %WALLET% --network %NETWORK% -- initialize --admin %ADMIN_ADDRESS%
+
+echo [OK] Registry contract initialized
+echo.
+
 REM Deploy escrow contract
-echo Step 3: Deploying agent_escrow contract...
+echo Step 4: Deploying agent_escrow contract...
 for /f "tokens=*" %%i in ('stellar contract deploy --wasm target\wasm32v1-none\release\agent_escrow.wasm --source %WALLET% --network %NETWORK%') do set ESCROW_CONTRACT_ID=%%i
 
 echo [OK] Escrow contract deployed
@@ -58,18 +72,14 @@ echo    Contract ID: %ESCROW_CONTRACT_ID%
 echo.
 
 REM Initialize escrow contract
-echo Step 4: Initializing agent_escrow contract...
-stellar contract invoke --id %ESCROW_CONTRACT_ID% --source %WALLET% --network %NETWORK% -- initialize
+echo Step 5: Initializing agent_escrow contract...
+stellar contract invoke --id %ESCROW_CONTRACT_ID% --source %WALLET% --network %NETWORK% -- initialize --admin %ADMIN_ADDRESS% --registry %REGISTRY_CONTRACT_ID% --arbiter %ADMIN_ADDRESS% --fee_bps 0
 
-echo [OK] Escrow contract initialized
-echo.
+REM Accept native XLM for jobs
+for /f "tokens=*" %%i in ('stellar contract id asset --asset native --network %NETWORK%') do set NATIVE_TOKEN_ID=%%i
+stellar contract invoke --id %ESCROW_CONTRACT_ID% --source %WALLET% --network %NETWORK% -- add_supported_token --token %NATIVE_TOKEN_ID% --min_amount 0
 
-REM Deploy registry contract
-echo Step 5: Deploying agent_registry contract...
-for /f "tokens=*" %%i in ('stellar contract deploy --wasm target\wasm32v1-none\release\agent_registry.wasm --source %WALLET% --network %NETWORK%') do set REGISTRY_CONTRACT_ID=%%i
-
-echo [OK] Registry contract deployed
-echo    Contract ID: %REGISTRY_CONTRACT_ID%
+echo [OK] Escrow contract initialized
 echo.
 
 REM Save contract IDs to .env.local
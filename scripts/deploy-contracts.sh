@@ -41,8 +41,30 @@ stellar contract optimize \
 echo "✅ Contracts optimized"
 echo ""
 
+# Deploy registry contract
+echo "Step 3: Deploying agent_registry contract..."
+REGISTRY_CONTRACT_ID=$(stellar contract deploy \
+  --wasm target/wasm32v1-none/release/agent_registry.wasm \
+  --source $WALLET \
+  --network $NETWORK)
+
+echo "✅ Registry contract deployed"
+echo "   Contract ID: $REGISTRY_CONTRACT_ID"
+echo ""
+
+stellar contract invoke \
+  --id $REGISTRY_CONTRACT_ID \
+  --source $WALLET \
+  --network $NETWORK \
+  -- \
+  initialize \
+  --admin $(stellar keys address $WALLET)
+
+echo "✅ Registry contract initialized"
+echo ""
+
 # Deploy escrow contract
-echo "Step 3: Deploying agent_escrow contract..."
+echo "Step 4: Deploying agent_escrow contract..."
 ESCROW_CONTRACT_ID=$(stellar contract deploy \
   --wasm target/wasm32v1-none/release/agent_escrow.wasm \
   --source $WALLET \
@@ -53,26 +75,30 @@ echo "   Contract ID: $ESCROW_CONTRACT_ID"
 echo ""
 
 # Initialize escrow contract
-echo "Step 4: Initializing agent_escrow contract..."
+echo "Step 5: Initializing agent_escrow contract..."
 stellar contract invoke \
   --id $ESCROW_CONTRACT_ID \
   --source $WALLET \
   --network $NETWORK \
   -- \
-  initialize
-
-echo "✅ Escrow contract initialized"
-echo ""
-
-# Deploy registry contract
-echo "Step 5: Deploying agent_registry contract..."
-REGISTRY_CONTRACT_ID=$(stellar contract deploy \
-  --wasm target/wasm32v1-none/release/agent_registry.wasm \
+  initialize \
+  --admin $(stellar keys address $WALLET) \
+  --registry $REGISTRY_CONTRACT_ID \
+  --arbiter $(stellar keys address $WALLET) \
+  --fee_bps 0
+
+# Accept native XLM for jobs
+NATIVE_TOKEN_ID=$(stellar contract id asset --asset native --network $NETWORK)
+stellar contract invoke \
+  --id $ESCROW_CONTRACT_ID \
   --source $WALLET \
-  --network $NETWORK)
+  --network $NETWORK \
+  -- \
+  add_supported_token \
+  --token $NATIVE_TOKEN_ID \
+  --min_amount 0
 
-echo "✅ Registry contract deployed"
-echo "   Contract ID: $REGISTRY_CONTRACT_ID"
+echo "✅ Escrow contract initialized"
 echo ""
 
 # Save contract IDs to .env.local